@@ -0,0 +1,421 @@
+use std::error::Error;
+use std::fmt;
+
+// THREE_HOURS_IN_SECONDS is computed as 60 * 60 * 3 in main.rs
+// this module does the same arithmetic in both directions: "3h" -> 10800 and 10800 -> "3h"
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+impl Unit {
+    // largest first, the order units are written in
+    pub const ALL: [Unit; 5] = [
+        Unit::Week,
+        Unit::Day,
+        Unit::Hour,
+        Unit::Minute,
+        Unit::Second,
+    ];
+
+    pub fn seconds(self) -> u64 {
+        match self {
+            Unit::Second => 1,
+            Unit::Minute => 60,
+            Unit::Hour => 60 * 60,
+            Unit::Day => 60 * 60 * 24,
+            Unit::Week => 60 * 60 * 24 * 7,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Second => "s",
+            Unit::Minute => "m",
+            Unit::Hour => "h",
+            Unit::Day => "d",
+            Unit::Week => "w",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Second => "second",
+            Unit::Minute => "minute",
+            Unit::Hour => "hour",
+            Unit::Day => "day",
+            Unit::Week => "week",
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Option<Unit> {
+        let unit = match suffix.to_ascii_lowercase().as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => Unit::Second,
+            "m" | "min" | "mins" | "minute" | "minutes" => Unit::Minute,
+            "h" | "hr" | "hrs" | "hour" | "hours" => Unit::Hour,
+            "d" | "day" | "days" => Unit::Day,
+            "w" | "wk" | "wks" | "week" | "weeks" => Unit::Week,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDurationError {
+    Empty,
+    MissingNumber(usize),
+    MissingUnit(usize),
+    UnknownUnit(String),
+    // "30m1h" or "1h2h": each unit may appear once, largest first
+    UnitOutOfOrder(Unit),
+    // ISO 8601 years and months have no fixed length in seconds
+    UnsupportedIsoDesignator(char),
+    InvalidIso(String),
+    Overflow,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseDurationError::Empty => write!(f, "empty duration"),
+            ParseDurationError::MissingNumber(at) => write!(f, "expected a number at byte {}", at),
+            ParseDurationError::MissingUnit(at) => write!(f, "expected a unit at byte {}", at),
+            ParseDurationError::UnknownUnit(unit) => write!(f, "unknown unit `{}`", unit),
+            ParseDurationError::UnitOutOfOrder(unit) => {
+                write!(f, "unit `{}` is repeated or out of order", unit.symbol())
+            }
+            ParseDurationError::UnsupportedIsoDesignator(c) => {
+                write!(f, "ISO 8601 designator `{}` has no fixed length", c)
+            }
+            ParseDurationError::InvalidIso(input) => {
+                write!(f, "`{}` is not an ISO 8601 duration", input)
+            }
+            ParseDurationError::Overflow => write!(f, "duration does not fit in u64 seconds"),
+        }
+    }
+}
+
+impl Error for ParseDurationError {}
+
+// parses either the compact form ("3h", "1h30m", "90s", "2d 4h"), what format_verbose writes
+// ("1 day, 2 hours"), or an ISO 8601 duration ("P1DT2H")
+pub fn parse(input: &str) -> Result<u64, ParseDurationError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ParseDurationError::Empty);
+    }
+    if input.starts_with('P') || input.starts_with('p') {
+        parse_iso8601(input)
+    } else {
+        parse_compact(input)
+    }
+}
+
+pub fn parse_compact(input: &str) -> Result<u64, ParseDurationError> {
+    let bytes = input.as_bytes();
+    let mut total: u64 = 0;
+    let mut previous: Option<Unit> = None;
+    let mut i = 0;
+
+    if input.trim().is_empty() {
+        return Err(ParseDurationError::Empty);
+    }
+
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if start == i {
+            return Err(ParseDurationError::MissingNumber(start));
+        }
+        let number: u64 = input[start..i]
+            .parse()
+            .map_err(|_| ParseDurationError::Overflow)?;
+
+        while i < bytes.len() && bytes[i] == b' ' {
+            i += 1;
+        }
+        let unit_start = i;
+        while i < bytes.len() && bytes[i].is_ascii_alphabetic() {
+            i += 1;
+        }
+        if unit_start == i {
+            return Err(ParseDurationError::MissingUnit(unit_start));
+        }
+        let suffix = &input[unit_start..i];
+        let unit = Unit::from_suffix(suffix)
+            .ok_or_else(|| ParseDurationError::UnknownUnit(suffix.to_string()))?;
+
+        if let Some(previous) = previous {
+            if unit >= previous {
+                return Err(ParseDurationError::UnitOutOfOrder(unit));
+            }
+        }
+        previous = Some(unit);
+
+        total = number
+            .checked_mul(unit.seconds())
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or(ParseDurationError::Overflow)?;
+
+        // format_verbose puts ", " between components; a comma needs another component after it
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i < bytes.len() && bytes[i] == b',' {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if i == bytes.len() {
+                return Err(ParseDurationError::MissingNumber(i));
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+// supports PnW and PnDTnHnMnS; years and months are rejected because their length depends on the calendar
+pub fn parse_iso8601(input: &str) -> Result<u64, ParseDurationError> {
+    let invalid = || ParseDurationError::InvalidIso(input.to_string());
+
+    let body = input
+        .strip_prefix('P')
+        .or_else(|| input.strip_prefix('p'))
+        .ok_or_else(invalid)?;
+    if body.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total: u64 = 0;
+    let mut in_time = false;
+    let mut previous: Option<Unit> = None;
+    let mut number = String::new();
+    let mut saw_component = false;
+    let mut saw_time_component = false;
+
+    for c in body.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let designator = c.to_ascii_uppercase();
+        if designator == 'T' {
+            if in_time || !number.is_empty() {
+                return Err(invalid());
+            }
+            in_time = true;
+            continue;
+        }
+        if number.is_empty() {
+            return Err(invalid());
+        }
+
+        let unit = match (designator, in_time) {
+            ('W', false) => Unit::Week,
+            ('D', false) => Unit::Day,
+            ('H', true) => Unit::Hour,
+            ('M', true) => Unit::Minute,
+            ('S', true) => Unit::Second,
+            ('Y', false) | ('M', false) => {
+                return Err(ParseDurationError::UnsupportedIsoDesignator(designator))
+            }
+            _ => return Err(invalid()),
+        };
+        if let Some(previous) = previous {
+            if unit >= previous {
+                return Err(ParseDurationError::UnitOutOfOrder(unit));
+            }
+        }
+        previous = Some(unit);
+
+        let value: u64 = number.parse().map_err(|_| ParseDurationError::Overflow)?;
+        number.clear();
+        saw_component = true;
+        saw_time_component |= in_time;
+        total = value
+            .checked_mul(unit.seconds())
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or(ParseDurationError::Overflow)?;
+    }
+
+    // trailing digits without a designator, or a "T" with nothing after it
+    if !number.is_empty() || !saw_component || (in_time && !saw_time_component) {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
+
+// splits seconds into (count, unit) pairs, never using a unit bigger than `largest`
+// zero components are left out, so 3600 becomes [(1, Hour)]
+pub fn components(seconds: u64, largest: Unit) -> Vec<(u64, Unit)> {
+    let mut remaining = seconds;
+    let mut parts = Vec::new();
+
+    for unit in Unit::ALL.iter().copied().filter(|unit| *unit <= largest) {
+        let count = remaining / unit.seconds();
+        remaining %= unit.seconds();
+        if count > 0 {
+            parts.push((count, unit));
+        }
+    }
+
+    parts
+}
+
+// "1h30m", or "0s" for zero
+pub fn format_compact(seconds: u64, largest: Unit) -> String {
+    let parts = components(seconds, largest);
+    if parts.is_empty() {
+        return String::from("0s");
+    }
+    parts
+        .iter()
+        .map(|(count, unit)| format!("{}{}", count, unit.symbol()))
+        .collect()
+}
+
+// "1 hour, 30 minutes", or "0 seconds" for zero
+pub fn format_verbose(seconds: u64, largest: Unit) -> String {
+    let parts = components(seconds, largest);
+    if parts.is_empty() {
+        return String::from("0 seconds");
+    }
+    parts
+        .iter()
+        .map(|(count, unit)| {
+            let plural = if *count == 1 { "" } else { "s" };
+            format!("{} {}{}", count, unit.name(), plural)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// "PT1H30M"; weeks are folded into days because ISO 8601 does not mix W with other designators
+pub fn format_iso8601(seconds: u64) -> String {
+    let parts = components(seconds, Unit::Day);
+    if parts.is_empty() {
+        return String::from("PT0S");
+    }
+
+    let mut iso = String::from("P");
+    let mut in_time = false;
+    for (count, unit) in parts {
+        if unit < Unit::Day && !in_time {
+            iso.push('T');
+            in_time = true;
+        }
+        iso.push_str(&count.to_string());
+        iso.push_str(&unit.symbol().to_ascii_uppercase());
+    }
+    iso
+}
+
+pub fn durations(three_hours_in_seconds: u32) {
+    let three_hours = u64::from(three_hours_in_seconds);
+    assert_eq!(parse("3h"), Ok(three_hours));
+    assert_eq!(parse("PT3H"), Ok(three_hours));
+    assert_eq!(format_compact(three_hours, Unit::Week), "3h");
+    assert_eq!(format_compact(three_hours, Unit::Minute), "180m");
+    assert_eq!(format_verbose(three_hours, Unit::Week), "3 hours");
+
+    assert_eq!(parse("1h30m"), Ok(5400));
+    assert_eq!(parse("1h 30m"), Ok(5400));
+    assert_eq!(parse("90s"), Ok(90));
+    assert_eq!(parse("2 days 4 hours"), Ok(187_200));
+    assert_eq!(parse("1 day, 2 hours, 1 minute, 1 second"), Ok(93_661));
+    assert_eq!(parse("1h,30m"), Ok(5400));
+    assert_eq!(parse("P1DT2H"), Ok(93_600));
+    assert_eq!(parse("P2W"), Ok(1_209_600));
+    assert_eq!(
+        format_verbose(93_661, Unit::Week),
+        "1 day, 2 hours, 1 minute, 1 second"
+    );
+    assert_eq!(format_compact(93_600, Unit::Hour), "26h");
+    assert_eq!(format_iso8601(93_600), "P1DT2H");
+    assert_eq!(format_iso8601(0), "PT0S");
+
+    assert_eq!(parse(""), Err(ParseDurationError::Empty));
+    assert_eq!(parse("h"), Err(ParseDurationError::MissingNumber(0)));
+    assert_eq!(parse("10"), Err(ParseDurationError::MissingUnit(2)));
+    assert_eq!(parse("1h,"), Err(ParseDurationError::MissingNumber(3)));
+    assert_eq!(parse(",1h"), Err(ParseDurationError::MissingNumber(0)));
+    assert_eq!(parse("1h,, 2m"), Err(ParseDurationError::MissingNumber(3)));
+    assert_eq!(parse("1,h"), Err(ParseDurationError::MissingUnit(1)));
+    assert_eq!(
+        parse("5y"),
+        Err(ParseDurationError::UnknownUnit(String::from("y")))
+    );
+    assert_eq!(
+        parse("30m1h"),
+        Err(ParseDurationError::UnitOutOfOrder(Unit::Hour))
+    );
+    assert_eq!(
+        parse("1h2h"),
+        Err(ParseDurationError::UnitOutOfOrder(Unit::Hour))
+    );
+    assert_eq!(
+        parse("P1M"),
+        Err(ParseDurationError::UnsupportedIsoDesignator('M'))
+    );
+    assert!(parse("P1H").is_err());
+    assert!(parse("PT").is_err());
+    assert!(parse("P1DT").is_err());
+
+    // overflow is reported instead of wrapping, both in the number itself and in the multiplication
+    assert_eq!(parse("18446744073709551615s"), Ok(u64::MAX));
+    assert_eq!(
+        parse("18446744073709551616s"),
+        Err(ParseDurationError::Overflow)
+    );
+    assert_eq!(parse("30600000000000w"), Err(ParseDurationError::Overflow));
+    assert_eq!(
+        parse("18446744073709551615s1s"),
+        Err(ParseDurationError::UnitOutOfOrder(Unit::Second))
+    );
+    assert_eq!(
+        parse("1m18446744073709551575s"),
+        Err(ParseDurationError::Overflow)
+    );
+
+    // round trips: every formatter's output parses back to the same number of seconds
+    let edges = [
+        u64::MAX,
+        u64::MAX - 1,
+        u64::MAX / 2,
+        604_800 * 1_000_000_007,
+    ];
+    // every value up to two hours covers all minute and second combinations, then a sparse walk
+    // through ten weeks, every power of two and the values right at the u64 limit
+    let samples = (0..=7_200)
+        .chain((7_200..=6_048_000).step_by(997))
+        .chain((0..64).map(|bit| 1u64 << bit))
+        .chain(edges);
+    for seconds in samples {
+        for largest in Unit::ALL {
+            assert_eq!(parse(&format_compact(seconds, largest)), Ok(seconds));
+            assert_eq!(parse(&format_verbose(seconds, largest)), Ok(seconds));
+        }
+        assert_eq!(parse(&format_iso8601(seconds)), Ok(seconds));
+    }
+
+    println!(
+        "{} seconds is {}",
+        three_hours_in_seconds,
+        format_verbose(three_hours, Unit::Week)
+    );
+}
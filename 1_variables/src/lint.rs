@@ -0,0 +1,869 @@
+use std::fmt;
+
+// a small lint-style analyzer for the shadowing and mutability rules shown in main.rs
+// it tokenizes Rust source and tracks `let` bindings through block scopes, reporting:
+// - every binding that shadows an earlier one, with the scope depth of both
+// - `let mut` bindings that are never assigned, compound-assigned, borrowed as `&mut` or mutated by a method
+// - shadows that change the type of the name, like `let spaces = spaces.len();`
+//
+// it works on tokens, not on a full syntax tree, so types are only inferred from simple initializers
+// (literals, `String::from`, `.len()`, arithmetic on a known binding, or an explicit annotation),
+// and method calls only count as mutation when the method is in MUTATING_METHODS
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    Shadowed,
+    UnusedMut,
+    TypeChangingShadow,
+    UnterminatedLiteral,
+}
+
+impl DiagnosticKind {
+    pub fn code(self) -> &'static str {
+        match self {
+            DiagnosticKind::Shadowed => "shadowed_binding",
+            DiagnosticKind::UnusedMut => "unused_mut",
+            DiagnosticKind::TypeChangingShadow => "type_changing_shadow",
+            DiagnosticKind::UnterminatedLiteral => "unterminated_literal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub name: String,
+    pub line: usize,
+    pub column: usize,
+    // brace nesting depth of the binding the diagnostic points at
+    pub depth: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line,
+            self.column,
+            self.kind.code(),
+            self.message
+        )
+    }
+}
+
+// methods that take `&mut self`, so calling them on a binding requires it to be `mut`
+const MUTATING_METHODS: [&str; 24] = [
+    "push",
+    "push_str",
+    "pop",
+    "insert",
+    "remove",
+    "clear",
+    "truncate",
+    "extend",
+    "append",
+    "drain",
+    "retain",
+    "sort",
+    "sort_by",
+    "sort_by_key",
+    "sort_unstable",
+    "dedup",
+    "reverse",
+    "swap",
+    "resize",
+    "entry",
+    "get_mut",
+    "iter_mut",
+    "next",
+    "read_line",
+];
+
+const ASSIGNMENT_OPERATORS: [&str; 11] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "^=", "&=", "|=", "<<=", ">>=",
+];
+
+const INTEGER_TYPES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident,
+    Str,
+    Char,
+    Integer,
+    Float,
+    Lifetime,
+    Punct,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn is(&self, text: &str) -> bool {
+        self.kind != TokenKind::Str && self.kind != TokenKind::Char && self.text == text
+    }
+}
+
+const PUNCTUATION: [&str; 26] = [
+    "<<=", ">>=", "...", "..=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..", "#!", "#",
+];
+
+// a string or char literal that is still open at the end of the source, as in a half-typed file,
+// becomes a token that runs to the end and an unterminated_literal diagnostic
+fn tokenize(source: &str) -> (Vec<Token>, Vec<Diagnostic>) {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut diagnostics = Vec::new();
+    let (mut i, mut line, mut column) = (0, 1, 1);
+
+    // moves the cursor forward by n chars, keeping line and column up to date
+    // it stops at the end of the source, so an escape like `"\` at the very end can't step past it
+    let advance = |i: &mut usize, line: &mut usize, column: &mut usize, n: usize| {
+        for _ in 0..n {
+            if *i >= chars.len() {
+                break;
+            }
+            if chars.get(*i) == Some(&'\n') {
+                *line += 1;
+                *column = 1;
+            } else {
+                *column += 1;
+            }
+            *i += 1;
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (start, start_line, start_column) = (i, line, column);
+
+        if c.is_whitespace() {
+            advance(&mut i, &mut line, &mut column, 1);
+            continue;
+        }
+
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            continue;
+        }
+
+        if c == '/' && next == Some('*') {
+            let mut nesting = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    nesting += 1;
+                    advance(&mut i, &mut line, &mut column, 2);
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    nesting -= 1;
+                    advance(&mut i, &mut line, &mut column, 2);
+                    if nesting == 0 {
+                        break;
+                    }
+                } else {
+                    advance(&mut i, &mut line, &mut column, 1);
+                }
+            }
+            continue;
+        }
+
+        let kind;
+        // what to call the literal if it turns out to be unterminated
+        let mut unterminated = None;
+        // raw strings: r"...", r#"..."#, br"..."
+        let raw_start = if c == 'r' {
+            Some(i + 1)
+        } else if c == 'b' && next == Some('r') {
+            Some(i + 2)
+        } else {
+            None
+        };
+        let raw_hashes = raw_start.and_then(|at| {
+            let hashes = chars[at..].iter().take_while(|c| **c == '#').count();
+            (chars.get(at + hashes) == Some(&'"')).then_some((at, hashes))
+        });
+
+        if let Some((at, hashes)) = raw_hashes {
+            let open = at + hashes - i + 1;
+            advance(&mut i, &mut line, &mut column, open);
+            unterminated = Some("raw string");
+            while i < chars.len() {
+                let closes = chars[i] == '"'
+                    && chars[i + 1..]
+                        .iter()
+                        .take(hashes)
+                        .filter(|c| **c == '#')
+                        .count()
+                        == hashes;
+                if closes {
+                    advance(&mut i, &mut line, &mut column, 1 + hashes);
+                    unterminated = None;
+                    break;
+                }
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            kind = TokenKind::Str;
+        } else if c == '"' || (c == 'b' && next == Some('"')) {
+            let skip = if c == 'b' { 2 } else { 1 };
+            advance(&mut i, &mut line, &mut column, skip);
+            while i < chars.len() && chars[i] != '"' {
+                let step = if chars[i] == '\\' { 2 } else { 1 };
+                advance(&mut i, &mut line, &mut column, step);
+            }
+            if i < chars.len() {
+                advance(&mut i, &mut line, &mut column, 1);
+            } else {
+                unterminated = Some("string");
+            }
+            kind = TokenKind::Str;
+        } else if c == '\'' || (c == 'b' && next == Some('\'')) {
+            let at = if c == 'b' { i + 1 } else { i };
+            let is_char = chars.get(at + 1) == Some(&'\\') || chars.get(at + 2) == Some(&'\'');
+            if is_char {
+                let open = at - i + 1;
+                advance(&mut i, &mut line, &mut column, open);
+                while i < chars.len() && chars[i] != '\'' {
+                    let step = if chars[i] == '\\' { 2 } else { 1 };
+                    advance(&mut i, &mut line, &mut column, step);
+                }
+                if i < chars.len() {
+                    advance(&mut i, &mut line, &mut column, 1);
+                } else {
+                    unterminated = Some("character");
+                }
+                kind = TokenKind::Char;
+            } else {
+                advance(&mut i, &mut line, &mut column, 1);
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    advance(&mut i, &mut line, &mut column, 1);
+                }
+                kind = TokenKind::Lifetime;
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            kind = TokenKind::Ident;
+        } else if c.is_ascii_digit() {
+            let mut float = false;
+            while i < chars.len() {
+                let ch = chars[i];
+                let after = chars.get(i + 1).copied().unwrap_or(' ');
+                // `1..4` is a range and `1.max(2)` a method call, but `1.5` is a float
+                if ch == '.' && !float && after.is_ascii_digit() {
+                    float = true;
+                } else if !(ch.is_alphanumeric() || ch == '_') {
+                    break;
+                }
+                advance(&mut i, &mut line, &mut column, 1);
+            }
+            let text: String = chars[start..i].iter().collect();
+            let hex = text.starts_with("0x");
+            float |= text.ends_with("f32") || text.ends_with("f64") || (!hex && text.contains('e'));
+            kind = if float {
+                TokenKind::Float
+            } else {
+                TokenKind::Integer
+            };
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let length = PUNCTUATION
+                .iter()
+                .find(|p| rest.starts_with(*p))
+                .map_or(1, |p| p.len());
+            advance(&mut i, &mut line, &mut column, length);
+            kind = TokenKind::Punct;
+        }
+
+        if let Some(literal) = unterminated {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::UnterminatedLiteral,
+                name: literal.to_string(),
+                line: start_line,
+                column: start_column,
+                // the tokenizer doesn't know about blocks
+                depth: 0,
+                message: format!("unterminated {} literal", literal),
+            });
+        }
+        tokens.push(Token {
+            kind,
+            text: chars[start..i].iter().collect(),
+            line: start_line,
+            column: start_column,
+        });
+    }
+
+    (tokens, diagnostics)
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    name: String,
+    line: usize,
+    column: usize,
+    depth: usize,
+    mutable: bool,
+    mutated: bool,
+    ty: Option<String>,
+}
+
+// a `let` whose pattern has been read but whose initializer is still being walked
+struct PendingLet {
+    bindings: Vec<Binding>,
+    annotation: Option<String>,
+    init_start: usize,
+    depth: usize,
+    nesting: usize,
+}
+
+struct Analyzer {
+    tokens: Vec<Token>,
+    bindings: Vec<Binding>,
+    // indexes into `bindings`, one list per open block
+    scopes: Vec<Vec<usize>>,
+    // bindings introduced before their block opens: fn parameters, `for` and `if let` patterns
+    pending_block: Vec<Binding>,
+    pending_lets: Vec<PendingLet>,
+    // parentheses and brackets, which can contain `;` (as in `[i32; 5]`) without ending a statement
+    nesting: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+pub fn analyze(source: &str) -> Vec<Diagnostic> {
+    let (tokens, diagnostics) = tokenize(source);
+    let mut analyzer = Analyzer {
+        tokens,
+        bindings: Vec::new(),
+        scopes: vec![Vec::new()],
+        pending_block: Vec::new(),
+        pending_lets: Vec::new(),
+        nesting: 0,
+        diagnostics,
+    };
+    analyzer.run();
+    analyzer.diagnostics.sort_by_key(|d| (d.line, d.column));
+    analyzer.diagnostics
+}
+
+impl Analyzer {
+    fn depth(&self) -> usize {
+        self.scopes.len() - 1
+    }
+
+    fn text(&self, i: usize) -> &str {
+        self.tokens.get(i).map_or("", |t| t.text.as_str())
+    }
+
+    fn is(&self, i: usize, text: &str) -> bool {
+        self.tokens.get(i).is_some_and(|t| t.is(text))
+    }
+
+    fn run(&mut self) {
+        let mut i = 0;
+        while i < self.tokens.len() {
+            i = self.step(i);
+        }
+        while self.scopes.len() > 1 {
+            self.close_scope();
+        }
+        self.close_scope();
+    }
+
+    // handles the token at i and returns the index of the next token to look at
+    fn step(&mut self, i: usize) -> usize {
+        let token = &self.tokens[i];
+        if token.kind == TokenKind::Punct {
+            match token.text.as_str() {
+                "{" => {
+                    self.scopes.push(Vec::new());
+                    for binding in std::mem::take(&mut self.pending_block) {
+                        self.declare(binding);
+                    }
+                }
+                "}" if self.scopes.len() > 1 => self.close_scope(),
+                "(" | "[" => self.nesting += 1,
+                ")" | "]" => self.nesting = self.nesting.saturating_sub(1),
+                ";" => self.finish_let(i),
+                _ => {}
+            }
+            return i + 1;
+        }
+        if token.kind != TokenKind::Ident {
+            return i + 1;
+        }
+
+        match token.text.as_str() {
+            "let"
+                if self.is(i.wrapping_sub(1), "if")
+                    || self.is(i.wrapping_sub(1), "while")
+                    || self.is(i.wrapping_sub(1), "&&") =>
+            {
+                let (bindings, end) = self.pattern(i + 1, &["="]);
+                self.pending_block.extend(bindings);
+                end + 1
+            }
+            "let" => self.start_let(i),
+            "for" => {
+                let (bindings, end) = self.pattern(i + 1, &["in"]);
+                self.pending_block.extend(bindings);
+                end + 1
+            }
+            "fn" => self.function(i),
+            _ => {
+                self.check_mutation(i);
+                i + 1
+            }
+        }
+    }
+
+    // reads pattern identifiers up to one of the terminators at nesting level 0
+    // returns the bindings and the index of the terminator
+    fn pattern(&self, mut i: usize, terminators: &[&str]) -> (Vec<Binding>, usize) {
+        let mut bindings = Vec::new();
+        let mut nesting = 0;
+        let mut mutable = false;
+
+        while i < self.tokens.len() {
+            let token = &self.tokens[i];
+            if nesting == 0 && terminators.iter().any(|t| token.is(t)) {
+                break;
+            }
+            match token.text.as_str() {
+                "(" | "[" | "{" => nesting += 1,
+                ")" | "]" | "}" => nesting -= 1,
+                "mut" => mutable = true,
+                "ref" => {}
+                _ if token.kind == TokenKind::Ident => {
+                    let is_path = self.is(i + 1, "::") || self.is(i.wrapping_sub(1), "::");
+                    let is_variant = self.is(i + 1, "(") || self.is(i + 1, "{");
+                    let is_field_name = self.is(i + 1, ":") && nesting > 0;
+                    let is_constant = token.text.starts_with(char::is_uppercase);
+                    if !(is_path || is_variant || is_field_name || is_constant || token.text == "_")
+                    {
+                        bindings.push(Binding {
+                            name: token.text.clone(),
+                            line: token.line,
+                            column: token.column,
+                            depth: 0,
+                            mutable,
+                            mutated: false,
+                            ty: None,
+                        });
+                    }
+                    mutable = false;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        (bindings, i)
+    }
+
+    fn start_let(&mut self, i: usize) -> usize {
+        let (bindings, end) = self.pattern(i + 1, &["=", ":", ";"]);
+        let mut next = end;
+        let mut annotation = None;
+
+        if self.is(end, ":") {
+            let mut j = end + 1;
+            let mut nesting = 0;
+            let mut parts: Vec<&str> = Vec::new();
+            while j < self.tokens.len() {
+                let text = self.text(j);
+                if nesting == 0 && (text == "=" || text == ";") {
+                    break;
+                }
+                match text {
+                    "(" | "[" | "<" => nesting += 1,
+                    ")" | "]" | ">" => nesting -= 1,
+                    _ => {}
+                }
+                parts.push(text);
+                if text == ";" || text == "," {
+                    parts.push(" ");
+                }
+                j += 1;
+            }
+            annotation = Some(parts.concat());
+            next = j;
+        }
+
+        let init_start = if self.is(next, "=") { next + 1 } else { next };
+        self.pending_lets.push(PendingLet {
+            bindings,
+            annotation,
+            init_start,
+            depth: self.depth(),
+            nesting: self.nesting,
+        });
+        init_start
+    }
+
+    // the binding only becomes visible at the end of its statement, which is why
+    // `let x = x + 1;` reads the previous x
+    fn finish_let(&mut self, semicolon: usize) {
+        let ends_here = self.pending_lets.last().is_some_and(|pending| {
+            pending.depth == self.depth() && pending.nesting == self.nesting
+        });
+        if !ends_here {
+            return;
+        }
+        let pending = self.pending_lets.pop().expect("checked above");
+        let single = pending.bindings.len() == 1;
+        let ty = pending
+            .annotation
+            .clone()
+            .or_else(|| self.infer(pending.init_start, semicolon));
+
+        for mut binding in pending.bindings {
+            if single {
+                binding.ty = ty.clone();
+            }
+            self.declare(binding);
+        }
+    }
+
+    fn function(&mut self, i: usize) -> usize {
+        let mut j = i + 1;
+        while j < self.tokens.len() && !self.is(j, "(") {
+            j += 1;
+        }
+
+        // parameters are `pattern: Type` pairs separated by commas inside the parentheses
+        let mut nesting = 0;
+        let mut in_type = false;
+        let mut parameter_start = j + 1;
+        j += 1;
+        while j < self.tokens.len() {
+            let text = self.text(j);
+            match text {
+                "(" | "[" | "<" => nesting += 1,
+                ")" | "]" | ">" if nesting > 0 => nesting -= 1,
+                ")" => break,
+                ":" if nesting == 0 && !in_type => {
+                    let (bindings, _) = self.pattern(parameter_start, &[":"]);
+                    self.pending_block
+                        .extend(bindings.into_iter().filter(|b| b.name != "self"));
+                    in_type = true;
+                }
+                "," if nesting == 0 => {
+                    in_type = false;
+                    parameter_start = j + 1;
+                }
+                _ => {}
+            }
+            j += 1;
+        }
+
+        // skip the return type, the body starts at the next `{` (or there is none, as in a trait)
+        while j < self.tokens.len() && !self.is(j, "{") && !self.is(j, ";") {
+            j += 1;
+        }
+        if self.is(j, ";") {
+            self.pending_block.clear();
+        }
+        j
+    }
+
+    fn check_mutation(&mut self, i: usize) {
+        if self.is(i.wrapping_sub(1), ".") || self.is(i.wrapping_sub(1), "::") {
+            return;
+        }
+        let borrowed_mut = self.is(i.wrapping_sub(1), "mut") && self.is(i.wrapping_sub(2), "&");
+
+        // walk over field accesses and indexing: `x.field[0].other = 1` mutates x
+        let mut j = i + 1;
+        let mut method = None;
+        loop {
+            if self.is(j, ".")
+                && self
+                    .tokens
+                    .get(j + 1)
+                    .is_some_and(|t| t.kind == TokenKind::Ident)
+            {
+                if self.is(j + 2, "(") {
+                    method = Some(self.text(j + 1).to_string());
+                    break;
+                }
+                j += 2;
+            } else if self.is(j, "[") {
+                let mut nesting = 0;
+                while j < self.tokens.len() {
+                    match self.text(j) {
+                        "[" => nesting += 1,
+                        "]" => nesting -= 1,
+                        _ => {}
+                    }
+                    j += 1;
+                    if nesting == 0 {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+
+        let assigned = ASSIGNMENT_OPERATORS.iter().any(|op| self.is(j, op));
+        let mutating_call = method.is_some_and(|m| MUTATING_METHODS.contains(&m.as_str()));
+        if borrowed_mut || assigned || mutating_call {
+            let name = self.tokens[i].text.clone();
+            if let Some(index) = self.lookup(&name) {
+                self.bindings[index].mutated = true;
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .copied()
+            .find(|index| self.bindings[*index].name == name)
+    }
+
+    fn declare(&mut self, mut binding: Binding) {
+        binding.depth = self.depth();
+
+        if let Some(previous) = self.lookup(&binding.name).map(|i| self.bindings[i].clone()) {
+            self.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Shadowed,
+                name: binding.name.clone(),
+                line: binding.line,
+                column: binding.column,
+                depth: binding.depth,
+                message: format!(
+                    "`{}` at depth {} shadows the binding at {}:{} (depth {})",
+                    binding.name, binding.depth, previous.line, previous.column, previous.depth
+                ),
+            });
+
+            if let (Some(old), Some(new)) = (&previous.ty, &binding.ty) {
+                if !compatible(old, new) {
+                    self.diagnostics.push(Diagnostic {
+                        kind: DiagnosticKind::TypeChangingShadow,
+                        name: binding.name.clone(),
+                        line: binding.line,
+                        column: binding.column,
+                        depth: binding.depth,
+                        message: format!(
+                            "`{}` changes type from `{}` to `{}`",
+                            binding.name, old, new
+                        ),
+                    });
+                }
+            }
+        }
+
+        self.bindings.push(binding);
+        let index = self.bindings.len() - 1;
+        self.scopes
+            .last_mut()
+            .expect("there is always a scope")
+            .push(index);
+    }
+
+    fn close_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap_or_default();
+        for index in scope {
+            let binding = &self.bindings[index];
+            if binding.mutable && !binding.mutated {
+                self.diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::UnusedMut,
+                    name: binding.name.clone(),
+                    line: binding.line,
+                    column: binding.column,
+                    depth: binding.depth,
+                    message: format!("`{}` is declared `mut` but never mutated", binding.name),
+                });
+            }
+        }
+    }
+
+    // a best effort guess at the type of the initializer tokens in start..end
+    fn infer(&self, start: usize, end: usize) -> Option<String> {
+        let tokens = &self.tokens[start.min(end)..end];
+        let texts: Vec<&str> = tokens.iter().map(|t| t.text.as_str()).collect();
+
+        let literal = |token: &Token| match token.kind {
+            TokenKind::Str if token.text.starts_with('b') => Some(String::from("&[u8]")),
+            TokenKind::Str => Some(String::from("&str")),
+            TokenKind::Char if token.text.starts_with('b') => Some(String::from("u8")),
+            TokenKind::Char => Some(String::from("char")),
+            TokenKind::Integer | TokenKind::Float => Some(numeric_type(token)),
+            TokenKind::Ident if token.text == "true" || token.text == "false" => {
+                Some(String::from("bool"))
+            }
+            TokenKind::Ident => self
+                .lookup(&token.text)
+                .and_then(|i| self.bindings[i].ty.clone()),
+            _ => None,
+        };
+
+        match texts.as_slice() {
+            [] => None,
+            [_] => literal(&tokens[0]),
+            ["-", _] => literal(&tokens[1]),
+            ["String", "::", "from" | "new", ..] | ["format", "!", ..] => {
+                Some(String::from("String"))
+            }
+            [.., ".", "to_string" | "to_owned", "(", ")"] if texts[0] != "&" => {
+                Some(String::from("String"))
+            }
+            [.., ".", "len" | "count", "(", ")"] => Some(String::from("usize")),
+            [_, "+" | "-" | "*" | "/" | "%", _] => {
+                literal(&tokens[0]).filter(|ty| ty != "&str" && ty != "String")
+            }
+            _ => None,
+        }
+    }
+}
+
+fn numeric_type(token: &Token) -> String {
+    let suffix = INTEGER_TYPES
+        .iter()
+        .chain(["f32", "f64"].iter())
+        .find(|ty| token.text.ends_with(*ty) && !token.text.starts_with("0x"));
+    match (suffix, &token.kind) {
+        (Some(ty), _) => ty.to_string(),
+        (None, TokenKind::Float) => String::from("{float}"),
+        _ => String::from("{integer}"),
+    }
+}
+
+// an unsuffixed literal like `5` can still become any integer type, so only a clear mismatch counts
+fn compatible(a: &str, b: &str) -> bool {
+    let integer = |ty: &str| ty == "{integer}" || INTEGER_TYPES.contains(&ty);
+    let float = |ty: &str| ty == "{float}" || ty == "f32" || ty == "f64";
+    a == b
+        || (a == "{integer}" && integer(b))
+        || (b == "{integer}" && integer(a))
+        || (a == "{float}" && float(b))
+        || (b == "{float}" && float(a))
+}
+
+pub fn lint() {
+    // the analyzer runs over this crate's own main.rs, which is the training material for this chapter
+    let diagnostics = analyze(include_str!("main.rs"));
+    for diagnostic in &diagnostics {
+        println!("main.rs:{}", diagnostic);
+    }
+
+    let kinds = |kind: DiagnosticKind| {
+        diagnostics
+            .iter()
+            .filter(|d| d.kind == kind)
+            .map(|d| d.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds(DiagnosticKind::Shadowed),
+        ["x", "x", "x", "x", "spaces", "spaces", "spaces"]
+    );
+    assert_eq!(
+        kinds(DiagnosticKind::TypeChangingShadow),
+        ["spaces", "spaces", "spaces"]
+    );
+    // the same binding rustc warns about with "variable does not need to be mutable"
+    assert_eq!(kinds(DiagnosticKind::UnusedMut), ["spaces"]);
+
+    let source = "
+        fn add(mut total: i32, items: &mut Vec<i32>) -> i32 {
+            let mut count = 0;
+            let mut unused = 1;
+            for item in items.iter() {
+                let total = total + item;
+                count += 1;
+            }
+            let mut names = Vec::new();
+            names.push(\"ferris\");
+            let mut buffer = [0u8; 4];
+            buffer[0] = 1;
+            let mut borrowed = String::new();
+            change(&mut borrowed);
+            if let Some(count) = items.first() {
+                let count: String = count.to_string();
+            }
+            let label = \"items\";
+            let label = label.len();
+            total = count;
+            total
+        }
+    ";
+    let diagnostics = analyze(source);
+    let summary: Vec<(DiagnosticKind, &str, usize, usize)> = diagnostics
+        .iter()
+        .map(|d| (d.kind, d.name.as_str(), d.line, d.depth))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (DiagnosticKind::UnusedMut, "unused", 4, 1),
+            (DiagnosticKind::Shadowed, "total", 6, 2),
+            (DiagnosticKind::Shadowed, "count", 15, 2),
+            (DiagnosticKind::Shadowed, "count", 16, 2),
+            (DiagnosticKind::Shadowed, "label", 19, 1),
+            (DiagnosticKind::TypeChangingShadow, "label", 19, 1),
+        ]
+    );
+
+    // a half-typed file still gets analyzed, with the literal left open reported where it starts
+    let unterminated = |source: &str| {
+        analyze(source)
+            .into_iter()
+            .filter(|d| d.kind == DiagnosticKind::UnterminatedLiteral)
+            .map(|d| (d.message, d.line, d.column))
+            .collect::<Vec<_>>()
+    };
+    let string = || (String::from("unterminated string literal"), 1, 9);
+    assert_eq!(unterminated("let s = \"abc"), [string()]);
+    assert_eq!(unterminated("let s = \"\\"), [string()]);
+    assert_eq!(
+        unterminated("let x = 1;\n\"\\"),
+        [(String::from("unterminated string literal"), 2, 1)]
+    );
+    assert_eq!(
+        unterminated("let c = '\\"),
+        [(String::from("unterminated character literal"), 1, 9)]
+    );
+    assert_eq!(
+        unterminated("let c = b'\\"),
+        [(String::from("unterminated character literal"), 1, 9)]
+    );
+    assert_eq!(
+        unterminated("let r = r#\"abc\"\n"),
+        [(String::from("unterminated raw string literal"), 1, 9)]
+    );
+    assert_eq!(unterminated("let s = \"\\\"\";"), []);
+    let mut half_typed = analyze("let mut s = String::new();\ns.push_str(\"abc");
+    half_typed.retain(|d| d.kind != DiagnosticKind::UnterminatedLiteral);
+    assert_eq!(half_typed, []);
+
+    // any other files passed on the command line are analyzed too
+    for path in std::env::args().skip(1) {
+        match std::fs::read_to_string(&path) {
+            Ok(source) => {
+                for diagnostic in analyze(&source) {
+                    println!("{}:{}", path, diagnostic);
+                }
+            }
+            Err(error) => eprintln!("{}: {}", path, error),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+mod duration;
+mod lint;
+
 fn main() {
     // variables by default are immutable
     let x = 5;
@@ -14,6 +17,8 @@ fn main() {
     const THREE_HOURS_IN_SECONDS: u32 = 60 * 60 * 3;
 
     println!("The const value is: {}", THREE_HOURS_IN_SECONDS);
+    // src/duration.rs parses "3h" back into the same number and formats it as text
+    duration::durations(THREE_HOURS_IN_SECONDS);
 
     // variable shadowing
     let x = 5;
@@ -40,4 +45,7 @@ fn main() {
     let mut spaces = "   ";
     let spaces = spaces.len(); //  error
     println!("there are {} spaces", spaces);
+
+    // src/lint.rs finds the shadowed bindings, type-changing shadows and unneeded `mut` in this file
+    lint::lint();
 }
@@ -0,0 +1,339 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// the months array from the compound types chapter, now indexed by the Month enum below
+pub const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Month {
+    January = 1,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+impl Month {
+    pub const ALL: [Month; 12] = [
+        Month::January,
+        Month::February,
+        Month::March,
+        Month::April,
+        Month::May,
+        Month::June,
+        Month::July,
+        Month::August,
+        Month::September,
+        Month::October,
+        Month::November,
+        Month::December,
+    ];
+
+    // months are numbered 1..=12 like on a calendar, not like array indexes
+    pub fn from_number(number: u32) -> Option<Month> {
+        match number {
+            1..=12 => Some(Month::ALL[number as usize - 1]),
+            _ => None,
+        }
+    }
+
+    pub fn number(self) -> u32 {
+        self as u32
+    }
+
+    pub fn name(self) -> &'static str {
+        MONTH_NAMES[self as usize - 1]
+    }
+
+    pub fn abbreviation(self) -> &'static str {
+        &self.name()[..3]
+    }
+
+    pub fn days(self, year: i64) -> u32 {
+        match self {
+            Month::February if is_leap_year(year) => 29,
+            Month::February => 28,
+            Month::April | Month::June | Month::September | Month::November => 30,
+            _ => 31,
+        }
+    }
+
+    pub fn succ(self) -> Month {
+        Month::ALL[self as usize % 12]
+    }
+
+    pub fn pred(self) -> Month {
+        Month::ALL[(self as usize + 10) % 12]
+    }
+}
+
+impl fmt::Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMonthError {
+    input: String,
+}
+
+impl fmt::Display for ParseMonthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not a month name or abbreviation", self.input)
+    }
+}
+
+impl Error for ParseMonthError {}
+
+// accepts full names and three letter abbreviations in any case ("march", "Mar", "MAR"),
+// plus "Sept" because it is so common in written dates
+impl FromStr for Month {
+    type Err = ParseMonthError;
+
+    fn from_str(s: &str) -> Result<Month, ParseMonthError> {
+        let input = s.trim();
+        let found = Month::ALL.iter().copied().find(|month| {
+            input.eq_ignore_ascii_case(month.name())
+                || input.eq_ignore_ascii_case(month.abbreviation())
+                || (*month == Month::September && input.eq_ignore_ascii_case("Sept"))
+        });
+        found.ok_or_else(|| ParseMonthError {
+            input: s.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Weekday {
+    Monday = 1,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    // ISO 8601 numbering: Monday is 1 and Sunday is 7
+    pub fn iso_number(self) -> u32 {
+        self as u32
+    }
+
+    // days since Sunday, the column order `cal` uses
+    pub fn days_from_sunday(self) -> u32 {
+        self as u32 % 7
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        }
+    }
+}
+
+impl fmt::Display for Weekday {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// leap years are divisible by 4, except centuries, except every 400 years
+pub fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_year(year: i64) -> u32 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+pub fn is_valid_date(year: i64, month: Month, day: u32) -> bool {
+    day >= 1 && day <= month.days(year)
+}
+
+// 1-based day of the year, or None when the day does not exist in that month
+pub fn ordinal(year: i64, month: Month, day: u32) -> Option<u32> {
+    if !is_valid_date(year, month, day) {
+        return None;
+    }
+    let before: u32 = Month::ALL[..month as usize - 1]
+        .iter()
+        .map(|m| m.days(year))
+        .sum();
+    Some(before + day)
+}
+
+// number of days between 1970-01-01 and the given date in the proleptic Gregorian calendar,
+// which extends the current leap year rules backwards before 1582 and works for negative years too.
+// the era trick splits time into 400 year cycles, which always have the same number of days
+fn days_from_civil(year: i64, month: Month, day: u32) -> i64 {
+    let m = month.number() as i64;
+    let y = if m <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y.rem_euclid(400);
+    let day_of_year = (153 * ((m + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+pub fn day_of_week(year: i64, month: Month, day: u32) -> Option<Weekday> {
+    if !is_valid_date(year, month, day) {
+        return None;
+    }
+    // 1970-01-01 was a Thursday
+    let days = days_from_civil(year, month, day);
+    let index = (days + 3).rem_euclid(7) as usize;
+    Some(Weekday::ALL[index])
+}
+
+// an ISO year has 53 weeks when it starts on a Thursday, or on a Wednesday in a leap year
+pub fn iso_weeks_in_year(year: i64) -> u32 {
+    match day_of_week(year, Month::January, 1) {
+        Some(Weekday::Thursday) => 53,
+        Some(Weekday::Wednesday) if is_leap_year(year) => 53,
+        _ => 52,
+    }
+}
+
+// ISO 8601 week date: weeks start on Monday and week 1 is the week containing the first Thursday.
+// days at the start of January can belong to the last week of the previous year and days at the end
+// of December to week 1 of the next one, so the ISO year is returned along with the week number
+pub fn iso_week(year: i64, month: Month, day: u32) -> Option<(i64, u32)> {
+    let ordinal = ordinal(year, month, day)? as i64;
+    let weekday = day_of_week(year, month, day)?.iso_number() as i64;
+    let week = (ordinal - weekday + 10) / 7;
+
+    if week < 1 {
+        Some((year - 1, iso_weeks_in_year(year - 1)))
+    } else if week as u32 > iso_weeks_in_year(year) {
+        Some((year + 1, 1))
+    } else {
+        Some((year, week as u32))
+    }
+}
+
+// renders a month the way the unix `cal` utility does: a centered title,
+// a Sunday-first header and one row per week with right aligned day numbers
+pub fn month_grid(year: i64, month: Month) -> String {
+    const WIDTH: usize = 20;
+
+    let mut grid = format!("{:^width$}", format!("{} {}", month, year), width = WIDTH);
+    grid = grid.trim_end().to_string();
+    grid.push('\n');
+    grid.push_str("Su Mo Tu We Th Fr Sa\n");
+
+    let first = day_of_week(year, month, 1).expect("every month has a first day");
+    let blanks = (0..first.days_from_sunday()).map(|_| String::from("  "));
+    let days = (1..=month.days(year)).map(|day| format!("{:>2}", day));
+    let cells: Vec<String> = blanks.chain(days).collect();
+
+    for week in cells.chunks(7) {
+        grid.push_str(week.join(" ").trim_end());
+        grid.push('\n');
+    }
+
+    grid
+}
+
+pub fn print_month(year: i64, month: Month) {
+    print!("{}", month_grid(year, month));
+}
+
+pub fn calendar() {
+    // the array is still a plain [&str; 12], but now every entry has a typed Month behind it
+    for (index, name) in MONTH_NAMES.iter().enumerate() {
+        let month = Month::from_number(index as u32 + 1).unwrap();
+        assert_eq!(month.name(), *name);
+        assert_eq!(name.parse::<Month>(), Ok(month));
+    }
+
+    assert_eq!("feb".parse::<Month>(), Ok(Month::February));
+    assert_eq!("SEPT".parse::<Month>(), Ok(Month::September));
+    assert!("Febtember".parse::<Month>().is_err());
+    assert_eq!(Month::December.succ(), Month::January);
+    assert_eq!(Month::January.pred(), Month::December);
+
+    // leap years
+    assert_eq!(Month::February.days(2024), 29);
+    assert_eq!(Month::February.days(1900), 28);
+    assert_eq!(Month::February.days(2000), 29);
+    assert_eq!(days_in_year(2023), 365);
+
+    // day of week, including dates before the Gregorian reform and before year 1
+    assert_eq!(
+        day_of_week(1970, Month::January, 1),
+        Some(Weekday::Thursday)
+    );
+    assert_eq!(
+        day_of_week(2000, Month::January, 1),
+        Some(Weekday::Saturday)
+    );
+    assert_eq!(day_of_week(1582, Month::October, 4), Some(Weekday::Monday));
+    assert_eq!(day_of_week(0, Month::January, 1), Some(Weekday::Saturday));
+    assert_eq!(day_of_week(2023, Month::February, 29), None);
+
+    // ISO weeks, including the ones that spill into the neighbouring year
+    assert_eq!(iso_week(2021, Month::January, 3), Some((2020, 53)));
+    assert_eq!(iso_week(2021, Month::January, 4), Some((2021, 1)));
+    assert_eq!(iso_week(2024, Month::December, 30), Some((2025, 1)));
+    assert_eq!(iso_week(2026, Month::October, 19), Some((2026, 43)));
+    assert_eq!(iso_weeks_in_year(2020), 53);
+    assert_eq!(iso_weeks_in_year(2021), 52);
+
+    assert_eq!(
+        month_grid(2021, Month::February),
+        concat!(
+            "   February 2021\n",
+            "Su Mo Tu We Th Fr Sa\n",
+            "    1  2  3  4  5  6\n",
+            " 7  8  9 10 11 12 13\n",
+            "14 15 16 17 18 19 20\n",
+            "21 22 23 24 25 26 27\n",
+            "28\n",
+        )
+    );
+
+    print_month(2026, Month::October);
+}
@@ -0,0 +1,167 @@
+// helpers for the compound types from main.rs: tuples and fixed size arrays
+// the length N is part of an array's type, so with const generics the compiler keeps track of it for us
+
+/// pairs up two arrays element by element; both must have the same length or the call will not compile
+///
+/// ```
+/// # use types::compound::zip;
+/// assert_eq!(zip([1, 2, 3], ['a', 'b', 'c']), [(1, 'a'), (2, 'b'), (3, 'c')]);
+/// ```
+///
+/// ```compile_fail,E0308
+/// # use types::compound::zip;
+/// zip([1, 2, 3], ['a', 'b']); // error: expected an array with a size of 3, found one with a size of 2
+/// ```
+pub fn zip<T, U, const N: usize>(a: [T; N], b: [U; N]) -> [(T, U); N] {
+    let mut b = b.into_iter();
+    a.map(|x| (x, b.next().expect("both arrays have N elements")))
+}
+
+// like the built-in array map, but also passes the index of each element
+pub fn map_indexed<T, U, const N: usize>(a: [T; N], mut f: impl FnMut(usize, T) -> U) -> [U; N] {
+    let mut index = 0;
+    a.map(|x| {
+        let mapped = f(index, x);
+        index += 1;
+        mapped
+    })
+}
+
+/// every run of W neighbouring elements, as fixed size arrays instead of slices
+/// a window that is empty or longer than the array is a compile time error
+///
+/// ```
+/// # use types::compound::windows;
+/// assert_eq!(windows::<_, 3, 3>(&[1, 2, 3]).count(), 1);
+/// ```
+///
+/// ```compile_fail,E0080
+/// # use types::compound::windows;
+/// windows::<_, 3, 4>(&[1, 2, 3]).count(); // error: window size must be between 1 and the array length
+/// ```
+///
+/// ```compile_fail,E0080
+/// # use types::compound::windows;
+/// windows::<_, 3, 0>(&[1, 2, 3]).count();
+/// ```
+pub fn windows<T, const N: usize, const W: usize>(a: &[T; N]) -> impl Iterator<Item = &[T; W]> {
+    const {
+        assert!(
+            W > 0 && W <= N,
+            "window size must be between 1 and the array length"
+        )
+    };
+    a.windows(W)
+        .map(|window| window.try_into().expect("windows(W) yields W elements"))
+}
+
+// turns N rows of M columns into M rows of N columns
+pub fn transpose<T, const M: usize, const N: usize>(matrix: [[T; M]; N]) -> [[T; N]; M] {
+    let mut rows = matrix.map(|row| row.into_iter());
+    std::array::from_fn(|_| {
+        rows.each_mut()
+            .map(|row| row.next().expect("every row has M elements"))
+    })
+}
+
+/// indexing with a constant is checked when the program is built,
+/// while `a[i]` with a runtime i can only panic when it runs
+///
+/// ```
+/// # use types::compound::get;
+/// assert_eq!(*get::<2, _, 3>(&[1, 2, 3]), 3);
+/// ```
+///
+/// ```compile_fail,E0080
+/// # use types::compound::get;
+/// get::<3, _, 3>(&[1, 2, 3]); // error: index out of bounds
+/// ```
+pub fn get<const I: usize, T, const N: usize>(a: &[T; N]) -> &T {
+    const { assert!(I < N, "index out of bounds") };
+    &a[I]
+}
+
+/// converts a tuple whose fields all have the same type into an array, (1, 2, 3) -> [1, 2, 3]
+/// tuples with mixed types like (i32, f64, u8) have no implementation, so into_array does not exist for them
+///
+/// ```
+/// # use types::compound::IntoArray;
+/// assert_eq!((500, 6, 1).into_array(), [500, 6, 1]);
+/// ```
+///
+/// ```compile_fail,E0599
+/// # use types::compound::IntoArray;
+/// (500, 6.4, 1).into_array(); // error: no method named `into_array` found for tuple `({integer}, {float}, {integer})`
+/// ```
+pub trait IntoArray<T, const N: usize> {
+    fn into_array(self) -> [T; N];
+}
+
+// the reverse direction, [1, 2, 3] -> (1, 2, 3)
+pub trait FromArray<T, const N: usize> {
+    fn from_array(array: [T; N]) -> Self;
+}
+
+macro_rules! homogeneous_tuple {
+    (@type $_field:ident) => { T };
+    ($len:literal => $($field:ident)+) => {
+        impl<T> IntoArray<T, $len> for ($(homogeneous_tuple!(@type $field),)+) {
+            fn into_array(self) -> [T; $len] {
+                let ($($field,)+) = self;
+                [$($field),+]
+            }
+        }
+
+        impl<T> FromArray<T, $len> for ($(homogeneous_tuple!(@type $field),)+) {
+            fn from_array(array: [T; $len]) -> Self {
+                let [$($field),+] = array;
+                ($($field,)+)
+            }
+        }
+    };
+}
+
+homogeneous_tuple!(1 => a);
+homogeneous_tuple!(2 => a b);
+homogeneous_tuple!(3 => a b c);
+homogeneous_tuple!(4 => a b c d);
+homogeneous_tuple!(5 => a b c d e);
+homogeneous_tuple!(6 => a b c d e f);
+homogeneous_tuple!(7 => a b c d e f g);
+homogeneous_tuple!(8 => a b c d e f g h);
+homogeneous_tuple!(9 => a b c d e f g h i);
+homogeneous_tuple!(10 => a b c d e f g h i j);
+homogeneous_tuple!(11 => a b c d e f g h i j k);
+homogeneous_tuple!(12 => a b c d e f g h i j k l);
+
+pub fn compound() {
+    let a: [i32; 5] = [1, 2, 3, 4, 5];
+
+    assert_eq!(zip(a, [3; 5]), [(1, 3), (2, 3), (3, 3), (4, 3), (5, 3)]);
+
+    assert_eq!(map_indexed(a, |i, x| x * i as i32), [0, 2, 6, 12, 20]);
+    assert_eq!(a.map(|x| x * 10), [10, 20, 30, 40, 50]);
+
+    let pairs: Vec<&[i32; 2]> = windows(&a).collect();
+    assert_eq!(pairs, [&[1, 2], &[2, 3], &[3, 4], &[4, 5]]);
+    let sums: Vec<i32> = windows::<_, 5, 3>(&a).map(|[x, y, z]| x + y + z).collect();
+    assert_eq!(sums, [6, 9, 12]);
+
+    let matrix = [[1, 2, 3], [4, 5, 6]];
+    assert_eq!(transpose(matrix), [[1, 4], [2, 5], [3, 6]]);
+    assert_eq!(transpose(transpose(matrix)), matrix);
+
+    assert_eq!(*get::<4, _, 5>(&a), 5);
+    // the calls these helpers reject are the compile_fail examples above them, which `cargo test` checks
+    // the compiler also rejects indexing an array with a constant that is out of range:
+    // let _sixth = a[5]; // error: this operation will panic at runtime, index out of bounds
+
+    let tup = (500, 600, 700);
+    assert_eq!(tup.into_array(), [500, 600, 700]);
+    assert_eq!(<(i32, i32, i32)>::from_array([1, 2, 3]), (1, 2, 3));
+    let (x, y) = FromArray::from_array(["x", "y"]);
+    assert_eq!((x, y), ("x", "y"));
+
+    // tuples are bounds checked at compile time as well
+    // let _fourth = tup.3; // error: no field `3` on type `({integer}, {integer}, {integer})`
+}
@@ -0,0 +1,190 @@
+use std::mem::{align_of, offset_of, size_of};
+
+// how the types from main.rs are laid out in memory on the target this was compiled for
+// the output is plain text with fixed columns, so reports from different targets can be compared with diff,
+// for example a 64-bit build against one for a 32-bit target where isize and usize shrink to 4 bytes
+
+pub struct TypeLayout {
+    pub name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    pub option_size: usize,
+}
+
+impl TypeLayout {
+    pub fn of<T>(name: &'static str) -> TypeLayout {
+        TypeLayout {
+            name,
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            option_size: size_of::<Option<T>>(),
+        }
+    }
+
+    // a niche is a bit pattern the type can never hold, like 2 for bool or 0 for a reference
+    // Option<T> stores None in that pattern, so it needs no extra tag and is as big as T itself
+    pub fn has_niche(&self) -> bool {
+        self.option_size == self.size
+    }
+}
+
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+// the compiler may reorder tuple fields to reduce padding, so fields are listed by offset, not by index
+pub struct TupleLayout {
+    pub layout: TypeLayout,
+    pub fields: Vec<FieldLayout>,
+}
+
+impl TupleLayout {
+    // (offset, size) of every gap between fields and after the last one
+    pub fn padding(&self) -> Vec<(usize, usize)> {
+        let mut gaps = Vec::new();
+        let mut end = 0;
+        for field in &self.fields {
+            if field.offset > end {
+                gaps.push((end, field.offset - end));
+            }
+            end = end.max(field.offset + field.size);
+        }
+        if self.layout.size > end {
+            gaps.push((end, self.layout.size - end));
+        }
+        gaps
+    }
+}
+
+macro_rules! tuple_layout {
+    ($name:literal, ($($ty:ty),+), $($index:tt : $field:literal),+) => {{
+        type Tuple = ($($ty,)+);
+        let mut fields = vec![$(FieldLayout {
+            name: $field,
+            offset: offset_of!(Tuple, $index),
+            size: size_of::<$ty>(),
+        }),+];
+        fields.sort_by_key(|field| field.offset);
+        TupleLayout {
+            layout: TypeLayout::of::<Tuple>($name),
+            fields,
+        }
+    }};
+}
+
+pub fn scalar_layouts() -> Vec<TypeLayout> {
+    vec![
+        TypeLayout::of::<i8>("i8"),
+        TypeLayout::of::<u8>("u8"),
+        TypeLayout::of::<i16>("i16"),
+        TypeLayout::of::<u16>("u16"),
+        TypeLayout::of::<i32>("i32"),
+        TypeLayout::of::<u32>("u32"),
+        TypeLayout::of::<i64>("i64"),
+        TypeLayout::of::<u64>("u64"),
+        TypeLayout::of::<i128>("i128"),
+        TypeLayout::of::<u128>("u128"),
+        TypeLayout::of::<isize>("isize"),
+        TypeLayout::of::<usize>("usize"),
+        TypeLayout::of::<f32>("f32"),
+        TypeLayout::of::<f64>("f64"),
+        TypeLayout::of::<bool>("bool"),
+        TypeLayout::of::<char>("char"),
+        TypeLayout::of::<()>("()"),
+        TypeLayout::of::<&str>("&str"),
+        TypeLayout::of::<[i32; 5]>("[i32; 5]"),
+        TypeLayout::of::<[&str; 12]>("[&str; 12]"),
+    ]
+}
+
+pub fn tuple_layouts() -> Vec<TupleLayout> {
+    vec![
+        tuple_layout!("(i32, f64, u8)", (i32, f64, u8), 0: "i32", 1: "f64", 2: "u8"),
+        tuple_layout!("(u8, u32)", (u8, u32), 0: "u8", 1: "u32"),
+        tuple_layout!("(bool, char)", (bool, char), 0: "bool", 1: "char"),
+        tuple_layout!("(u8, isize, u8)", (u8, isize, u8), 0: "u8", 1: "isize", 2: "u8"),
+    ]
+}
+
+fn row(layout: &TypeLayout) -> String {
+    let niche = if layout.has_niche() { "yes" } else { "no" };
+    format!(
+        "{:<16} {:>5} {:>6} {:>6} {:>10}\n",
+        layout.name, layout.size, layout.align, niche, layout.option_size
+    )
+}
+
+pub fn layout_report() -> String {
+    let endian = if cfg!(target_endian = "little") {
+        "little"
+    } else {
+        "big"
+    };
+    let mut report = format!(
+        "# target: {}-{}, usize is {} bits, {} endian\n",
+        std::env::consts::ARCH,
+        std::env::consts::OS,
+        usize::BITS,
+        endian
+    );
+    report += &format!(
+        "{:<16} {:>5} {:>6} {:>6} {:>10}\n",
+        "type", "size", "align", "niche", "Option<T>"
+    );
+    for layout in scalar_layouts() {
+        report += &row(&layout);
+    }
+
+    for tuple in tuple_layouts() {
+        report += "\n";
+        report += &row(&tuple.layout);
+
+        // fields and padding, merged in offset order
+        let mut lines: Vec<(usize, String)> = tuple
+            .fields
+            .iter()
+            .map(|f| {
+                (
+                    f.offset,
+                    format!("  offset {:>3}  size {:>3}  {}\n", f.offset, f.size, f.name),
+                )
+            })
+            .collect();
+        for (offset, size) in tuple.padding() {
+            lines.push((
+                offset,
+                format!("  offset {:>3}  size {:>3}  (padding)\n", offset, size),
+            ));
+        }
+        lines.sort_by_key(|(offset, _)| *offset);
+        for (_, line) in lines {
+            report += &line;
+        }
+    }
+
+    report
+}
+
+pub fn layout() {
+    // these hold on every target Rust supports
+    assert_eq!(size_of::<char>(), 4);
+    assert_eq!(size_of::<()>(), 0);
+    assert_eq!(size_of::<isize>(), size_of::<usize>());
+    assert!(TypeLayout::of::<bool>("bool").has_niche());
+    assert!(TypeLayout::of::<char>("char").has_niche());
+    assert!(TypeLayout::of::<&str>("&str").has_niche());
+    assert!(!TypeLayout::of::<u32>("u32").has_niche());
+    assert!(!TypeLayout::of::<()>("()").has_niche());
+
+    // a tuple is never smaller than its fields and always a multiple of its alignment
+    for tuple in tuple_layouts() {
+        let fields: usize = tuple.fields.iter().map(|f| f.size).sum();
+        let padding: usize = tuple.padding().iter().map(|(_, size)| size).sum();
+        assert_eq!(fields + padding, tuple.layout.size);
+        assert_eq!(tuple.layout.size % tuple.layout.align, 0);
+    }
+
+    print!("{}", layout_report());
+}
@@ -0,0 +1,3 @@
+// compound.rs is a library module so that `cargo test` can run its examples,
+// including the compile_fail ones that show what the compiler rejects
+pub mod compound;
@@ -1,3 +1,8 @@
+mod calendar;
+mod layout;
+
+use types::compound;
+
 fn main() {
     // scalar types
     // Rust has four primary scalar types:
@@ -78,12 +83,13 @@ fn main() {
     let _tup: (i32, f64, u8) = (500, 6.4, 1);
 
     // getting values from tuple (destructuring)
-    let (_x, _y, _z) = tup;
+    let (_x, _y, _z) = _tup;
 
     // we can also access a tuple element directly by using a period (.) followed by the index of the value we want to access
-    let _five_hundred = x.0;
-    let _six_point_four = x.1;
-    let _one = x.2;
+    let _five_hundred = _tup.0;
+    let _six_point_four = _tup.1;
+    let _one = _tup.2;
+    // see src/compound.rs for converting tuples like this one into arrays
 
     // tuple without any values, (), is a special type that has only one value, also written ()
     // the type is called the `unit type` and the value is called the `unit value`
@@ -95,20 +101,9 @@ fn main() {
     // an array isn’t as flexible as the vector type, though
     // a vector is a similar collection type provided by the standard library that is allowed to grow or shrink in size
     let _a = [1, 2, 3, 4, 5];
-    let _months = [
-        "January",
-        "February",
-        "March",
-        "April",
-        "May",
-        "June",
-        "July",
-        "August",
-        "September",
-        "October",
-        "November",
-        "December",
-    ];
+    // the months of the year are a good fit for an array because there are always exactly 12 of them
+    // see src/calendar.rs, where the same array backs a Month enum
+    let _months: [&str; 12] = calendar::MONTH_NAMES;
 
     // you write an array’s type using square brackets with the type of each element, a semicolon, and then the number of elements in the array, like so
     let _a: [i32; 5] = [1, 2, 3, 4, 5];
@@ -120,4 +115,11 @@ fn main() {
     // you can access elements of an array using indexing, like this
     let _first = _a[0];
     let _second = _a[1];
+    // src/compound.rs has helpers which keep the array length in the type, so out of bounds access fails to compile
+    compound::compound();
+
+    calendar::calendar();
+
+    // src/layout.rs prints the size, alignment and niches of all the types above
+    layout::layout();
 }
@@ -0,0 +1,282 @@
+use std::fmt;
+use std::iter::FusedIterator;
+
+// plus_one from main.rs for every integer type, with the three answers Rust offers for what happens at the top:
+// checked gives back None, wrapping starts over at MIN, and saturating stays at MAX
+//
+// `x + 1` itself panics at MAX in debug builds and wraps in release builds, so plus_one(i32::MAX) is
+// i32::MIN once the program is built with --release
+
+pub trait Integer: Copy + PartialEq + PartialOrd + fmt::Debug + fmt::Display {
+    const ZERO: Self;
+    const ONE: Self;
+    const FIVE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+// the inherent methods are spelled out with the type, so that the trait methods don't end up calling themselves
+macro_rules! integer {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Integer for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+                const FIVE: $t = 5;
+                const MIN: $t = <$t>::MIN;
+                const MAX: $t = <$t>::MAX;
+
+                fn checked_add(self, rhs: $t) -> Option<$t> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: $t) -> Option<$t> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn wrapping_add(self, rhs: $t) -> $t {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                fn saturating_add(self, rhs: $t) -> $t {
+                    <$t>::saturating_add(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+integer! {
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+}
+
+pub fn five<T: Integer>() -> T {
+    T::FIVE
+}
+
+pub fn checked_plus_one<T: Integer>(x: T) -> Option<T> {
+    x.checked_add(T::ONE)
+}
+
+pub fn wrapping_plus_one<T: Integer>(x: T) -> T {
+    x.wrapping_add(T::ONE)
+}
+
+pub fn saturating_plus_one<T: Integer>(x: T) -> T {
+    x.saturating_add(T::ONE)
+}
+
+// a step can be negative for the signed types, and then it walks down towards MIN instead
+pub fn checked_step<T: Integer>(x: T, step: T) -> Option<T> {
+    x.checked_add(step)
+}
+
+pub fn wrapping_step<T: Integer>(x: T, step: T) -> T {
+    x.wrapping_add(step)
+}
+
+pub fn saturating_step<T: Integer>(x: T, step: T) -> T {
+    x.saturating_add(step)
+}
+
+// start, start + step, start + 2 * step, ... for as long as the values fit in T
+#[derive(Debug, Clone)]
+pub struct Steps<T> {
+    next: Option<T>,
+    step: T,
+}
+
+// like Iterator::step_by, a step of zero would never get anywhere, so it panics
+pub fn steps<T: Integer>(start: T, step: T) -> Steps<T> {
+    assert!(step != T::ZERO, "step must not be zero");
+    Steps {
+        next: Some(start),
+        step,
+    }
+}
+
+impl<T: Integer> Iterator for Steps<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let current = self.next?;
+        self.next = checked_step(current, self.step);
+        Some(current)
+    }
+}
+
+impl<T: Integer> FusedIterator for Steps<T> {}
+
+// what every step function has to agree on, checked against plain i128 arithmetic
+// i128 holds every value of the types up to 64 bits, and the sum of any two of them
+fn check_against_i128<T>(x: T, step: T)
+where
+    T: Integer + Into<i128> + TryFrom<i128>,
+{
+    let exact = x.into() + step.into();
+    let fits = T::try_from(exact).ok();
+
+    assert_eq!(checked_step(x, step), fits, "{} + {}", x, step);
+    let saturated = fits.unwrap_or(if exact > 0 { T::MAX } else { T::MIN });
+    assert_eq!(saturating_step(x, step), saturated, "{} + {}", x, step);
+    // wrapping is the exact sum modulo 2^bits, brought back into the range of T
+    let modulus = T::MAX.into() - T::MIN.into() + 1;
+    let wrapped = (exact - T::MIN.into()).rem_euclid(modulus) + T::MIN.into();
+    assert_eq!(wrapping_step(x, step).into(), wrapped, "{} + {}", x, step);
+}
+
+// the same, without a wider type to compare with, for u128 and i128
+fn check_consistency<T: Integer>(x: T, step: T) {
+    match checked_step(x, step) {
+        Some(sum) => {
+            assert_eq!(wrapping_step(x, step), sum, "{} + {}", x, step);
+            assert_eq!(saturating_step(x, step), sum, "{} + {}", x, step);
+        }
+        None => {
+            let limit = if step > T::ZERO { T::MAX } else { T::MIN };
+            assert_eq!(saturating_step(x, step), limit, "{} + {}", x, step);
+            // going over the top lands below the start, and going under the bottom lands above it
+            let wrapped = wrapping_step(x, step);
+            assert!(
+                (step > T::ZERO && wrapped < x) || (step < T::ZERO && wrapped > x),
+                "{} + {} wrapped to {}",
+                x,
+                step,
+                wrapped
+            );
+        }
+    }
+}
+
+fn check_plus_one<T: Integer>(x: T) {
+    if x == T::MAX {
+        assert_eq!(checked_plus_one(x), None);
+        assert_eq!(wrapping_plus_one(x), T::MIN);
+        assert_eq!(saturating_plus_one(x), T::MAX);
+    } else {
+        let next = checked_plus_one(x).expect("only MAX has no successor");
+        assert!(next > x);
+        assert_eq!(wrapping_plus_one(x), next);
+        assert_eq!(saturating_plus_one(x), next);
+    }
+}
+
+// the last value of steps() is as far as the type goes: one more step would not fit
+fn check_steps<T: Integer>(start: T, step: T) {
+    let mut count = 0;
+    let mut last = start;
+    for value in steps(start, step).take(1000) {
+        count += 1;
+        last = value;
+    }
+    if count < 1000 {
+        assert_eq!(checked_step(last, step), None, "steps({}, {})", start, step);
+    }
+}
+
+// MIN, MAX, zero, and the values close to each of them
+fn boundaries<T: Integer>() -> Vec<T> {
+    let mut values = Vec::new();
+    for edge in [T::MIN, T::ZERO, T::MAX] {
+        values.push(edge);
+        for offset in [T::ONE, T::FIVE] {
+            values.extend(edge.checked_add(offset));
+            values.extend(edge.checked_sub(offset));
+        }
+    }
+    values
+}
+
+macro_rules! check_widths {
+    ($($t:ty),*) => {
+        $(
+            let values = boundaries::<$t>();
+            for &x in &values {
+                check_plus_one(x);
+                for &step in &values {
+                    check_consistency(x, step);
+                    if step != 0 {
+                        check_steps(x, step);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! check_widths_against_i128 {
+    ($($t:ty),*) => {
+        $(
+            let values = boundaries::<$t>();
+            for &x in &values {
+                for &step in &values {
+                    check_against_i128(x, step);
+                }
+            }
+        )*
+    };
+}
+
+pub fn increment() {
+    assert_eq!(five::<u8>(), 5);
+    assert_eq!(five::<i128>(), 5);
+
+    // plus_one(i32::MAX) in main.rs would panic here in a debug build
+    assert_eq!(checked_plus_one(i32::MAX), None);
+    assert_eq!(wrapping_plus_one(i32::MAX), i32::MIN);
+    assert_eq!(saturating_plus_one(i32::MAX), i32::MAX);
+    assert_eq!(checked_plus_one(five::<u64>()), Some(6));
+
+    assert_eq!(checked_step(250u8, 5), Some(255));
+    assert_eq!(checked_step(250u8, 6), None);
+    assert_eq!(wrapping_step(250u8, 10), 4);
+    assert_eq!(saturating_step(-120i8, -10), i8::MIN);
+
+    let fives: Vec<u8> = steps(0, 50).collect();
+    assert_eq!(fives, [0, 50, 100, 150, 200, 250]);
+    let down: Vec<i8> = steps(-100, -10).collect();
+    assert_eq!(down, [-100, -110, -120]);
+    // the iterator stops at the top instead of wrapping, so it ends even without take()
+    assert_eq!(steps(u64::MAX - 2, 1).count(), 3);
+    assert_eq!(steps(0u128, u128::MAX / 4).last(), Some(u128::MAX / 4 * 4));
+
+    // every pair of u8 and of i8 values
+    for x in u8::MIN..=u8::MAX {
+        check_plus_one(x);
+        for step in u8::MIN..=u8::MAX {
+            check_against_i128(x, step);
+            check_consistency(x, step);
+        }
+    }
+    for x in i8::MIN..=i8::MAX {
+        check_plus_one(x);
+        for step in i8::MIN..=i8::MAX {
+            check_against_i128(x, step);
+            check_consistency(x, step);
+        }
+    }
+    // and for the number of values steps() yields, (MAX - start) / step + 1
+    for start in u8::MIN..=u8::MAX {
+        for step in 1..=u8::MAX {
+            let expected = (u8::MAX - start) as usize / step as usize + 1;
+            assert_eq!(steps(start, step).count(), expected);
+        }
+    }
+
+    // the wider types around their boundaries
+    check_widths!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+    check_widths_against_i128!(u16, u32, u64, i16, i32, i64);
+
+    println!(
+        "plus_one at the top of i32: checked {:?}, wrapping {}, saturating {}",
+        checked_plus_one(i32::MAX),
+        wrapping_plus_one(i32::MAX),
+        saturating_plus_one(i32::MAX)
+    );
+}
@@ -0,0 +1,410 @@
+// a tiny expression language built around the statements vs expressions part of main.rs:
+// integer arithmetic, `let` bindings, blocks whose value is their last expression, `if` as an expression,
+// and functions that return their last expression or leave early with `return`
+//
+//     fn plus_one(x: i32) -> i32 {
+//         x + 1
+//     }
+//     let y = {
+//         let x = 3;
+//         plus_one(x)
+//     };
+//     if y > 3 { y * 10 } else { 0 }
+//
+// programs are type checked before they run, so a function like plus_one_statement is rejected even if
+// nothing calls it
+//
+// run `cargo run -- repl` for an interactive prompt, or `cargo run -- run <file>` to run a file
+mod ast;
+mod eval;
+mod lexer;
+mod parser;
+mod typeck;
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+pub use eval::{Interpreter, Value, DEFAULT_MAX_DEPTH};
+pub use parser::MAX_NESTING;
+
+// byte offsets into the source text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // the span covering both self and other
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+    pub span: Span,
+    pub note: Option<String>,
+    pub help: Option<String>,
+    // the input stopped in the middle of something, so the REPL should read another line
+    pub incomplete: bool,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>, span: Span) -> Error {
+        Error {
+            message: message.into(),
+            span,
+            note: None,
+            help: None,
+            incomplete: false,
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Error {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Error {
+        self.help = Some(help.into());
+        self
+    }
+
+    fn incomplete(mut self) -> Error {
+        self.incomplete = true;
+        self
+    }
+
+    // formats the error the way rustc does, with the offending source line and carets under the span
+    //
+    //     error: expected expression, found statement (`let`)
+    //      --> example:1:10
+    //       |
+    //     1 | let x = (let y = 6);
+    //       |          ^^^
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line_number = source[..start].matches('\n').count() + 1;
+        let column = source[line_start..start].chars().count() + 1;
+        let text = &source[line_start..line_end];
+
+        let end = self.span.end.clamp(start, line_end);
+        let width = source[start..end].chars().count().max(1);
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        let mut rendered = format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}",
+            self.message,
+            gutter,
+            origin,
+            line_number,
+            column,
+            gutter,
+            line_number,
+            text,
+            gutter,
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        );
+        if let Some(note) = &self.note {
+            rendered.push_str(&format!("\n{} = note: {}", gutter, note));
+        }
+        if let Some(help) = &self.help {
+            rendered.push_str(&format!("\n{} = help: {}", gutter, help));
+        }
+        rendered
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub fn parse(source: &str) -> Result<ast::Block, Error> {
+    let tokens = lexer::tokenize(source)?;
+    parser::Parser::new(tokens).program()
+}
+
+impl Interpreter {
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        let program = parse(source)?;
+        self.run(&program)
+    }
+}
+
+pub fn eval(source: &str) -> Result<Value, Error> {
+    Interpreter::new().eval(source)
+}
+
+pub fn run_file(path: &str) -> Result<(), String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    match eval(&source) {
+        Ok(Value::Unit) => Ok(()),
+        Ok(value) => {
+            println!("{}", value);
+            Ok(())
+        }
+        Err(error) => Err(error.render(&source, path)),
+    }
+}
+
+// reads a line at a time; input that stops in the middle of an expression continues on the next line
+// bindings made with `let` stay available for the rest of the session
+pub fn repl() {
+    let mut interpreter = Interpreter::new();
+    let mut buffer = String::new();
+    let stdin = io::stdin();
+
+    loop {
+        print!("{}", if buffer.is_empty() { ">> " } else { ".. " });
+        io::stdout().flush().expect("Failed to flush stdout");
+
+        let mut line = String::new();
+        let read = stdin
+            .lock()
+            .read_line(&mut line)
+            .expect("Failed to read line");
+        if read == 0 {
+            println!();
+            break;
+        }
+        buffer.push_str(&line);
+
+        match parse(&buffer) {
+            Err(error) if error.incomplete && !line.trim().is_empty() => continue,
+            Err(error) => eprintln!("{}", error.render(&buffer, "<repl>")),
+            Ok(program) => match interpreter.run(&program) {
+                Ok(Value::Unit) => {}
+                Ok(value) => println!("{}", value),
+                Err(error) => eprintln!("{}", error.render(&buffer, "<repl>")),
+            },
+        }
+        buffer.clear();
+    }
+}
+
+pub fn interpreter() {
+    let source = "
+        let y = {
+            let x = 3;
+            x + 1
+        };
+        y
+    ";
+    assert_eq!(eval(source), Ok(Value::Int(4)));
+    // a semicolon after the last expression turns it into a statement, and the block evaluates to ()
+    assert_eq!(eval("let y = { let x = 3; x + 1; }; y"), Ok(Value::Unit));
+    assert_eq!(eval("2 + 3 * 4 - -1"), Ok(Value::Int(15)));
+    assert_eq!(eval("(2 + 3) * 4 % 7"), Ok(Value::Int(6)));
+    assert_eq!(
+        eval("let number = if true { 5 } else { 6 }; number"),
+        Ok(Value::Int(5))
+    );
+    assert_eq!(
+        eval("let n = 7; if n % 2 == 0 { 0 } else if n > 5 { 1 } else { 2 }"),
+        Ok(Value::Int(1))
+    );
+    // shadowing works like in Rust: the inner x only lives until the end of its block
+    assert_eq!(
+        eval("let x = 5; let x = x + 1; { let x = x * 2; x } + x"),
+        Ok(Value::Int(18))
+    );
+    assert_eq!(
+        eval("let t = true; t && !false || 1 / 0 == 0"),
+        Ok(Value::Bool(true))
+    );
+
+    let mut session = Interpreter::new();
+    assert_eq!(session.eval("let x = 40;"), Ok(Value::Unit));
+    assert_eq!(session.eval("x + 2"), Ok(Value::Int(42)));
+
+    let source = "let x = (let y = 6);";
+    let error = eval(source).unwrap_err();
+    assert_eq!(
+        error.message,
+        "expected expression, found statement (`let`)"
+    );
+    assert_eq!(error.span, Span::new(9, 12));
+    assert_eq!(
+        error.render(source, "example"),
+        "error: expected expression, found statement (`let`)\n \
+         --> example:1:10\n  \
+         |\n\
+         1 | let x = (let y = 6);\n  \
+         |          ^^^\n  \
+         = note: variable declaration using `let` is a statement"
+    );
+
+    let error = eval("let number = 3;\nif number { 1 } else { 2 }").unwrap_err();
+    assert_eq!(
+        error.message,
+        "mismatched types: expected `bool`, found `i32`"
+    );
+    assert_eq!(error.span, Span::new(19, 25));
+    assert_eq!(
+        eval("2147483647 + 1").unwrap_err().message,
+        "attempt to add with overflow"
+    );
+    assert_eq!(
+        eval("1 / (2 - 2)").unwrap_err().message,
+        "attempt to divide by zero"
+    );
+    assert_eq!(
+        eval("z + 1").unwrap_err().message,
+        "cannot find value `z` in this scope"
+    );
+    assert_eq!(
+        eval("1 2").unwrap_err().message,
+        "expected `;` or the end of the block, found `2`"
+    );
+    assert!(eval("let y = {").unwrap_err().incomplete);
+    assert!(!eval("let y = }").unwrap_err().incomplete);
+
+    // the functions from main.rs
+    let source = "
+        fn five() -> i32 {
+            5
+        }
+
+        fn plus_one(x: i32) -> i32 {
+            x + 1
+        }
+
+        let x = five();
+        plus_one(x)
+    ";
+    assert_eq!(eval(source), Ok(Value::Int(6)));
+    // functions can be called before they are defined, and can call themselves
+    let source = "
+        let result = factorial(10);
+        fn factorial(n: i32) -> i32 {
+            if n <= 1 { 1 } else { n * factorial(n - 1) }
+        }
+        result
+    ";
+    assert_eq!(eval(source), Ok(Value::Int(3628800)));
+    let source = "
+        fn fib(n: i32) -> i32 {
+            if n < 2 {
+                return n;
+            }
+            fib(n - 1) + fib(n - 2)
+        }
+        fib(20)
+    ";
+    assert_eq!(eval(source), Ok(Value::Int(6765)));
+    // a function body only sees its parameters, not the variables of the caller
+    assert_eq!(
+        eval("let y = 1; fn f() -> i32 { y } f()")
+            .unwrap_err()
+            .message,
+        "cannot find value `y` in this scope"
+    );
+    // `return` leaves the whole function, not just the block it is in
+    let source = "
+        fn sign(n: i32) -> i32 {
+            let magnitude = if n < 0 { return -1; } else { n };
+            if magnitude == 0 { 0 } else { 1 }
+        }
+        sign(-7) * 100 + sign(0) * 10 + sign(7)
+    ";
+    assert_eq!(eval(source), Ok(Value::Int(-99)));
+
+    let forever = "fn forever(n: i32) -> i32 { forever(n + 1) } forever(0)";
+    assert_eq!(
+        eval(forever).unwrap_err().message,
+        format!(
+            "reached the recursion limit while calling `forever` ({} nested calls)",
+            DEFAULT_MAX_DEPTH
+        )
+    );
+    assert_eq!(
+        Interpreter::with_max_depth(3)
+            .eval("fn count(n: i32) -> i32 { if n == 0 { 0 } else { 1 + count(n - 1) } } count(3)")
+            .unwrap_err()
+            .message,
+        "reached the recursion limit while calling `count` (3 nested calls)"
+    );
+    // nesting is limited when parsing, so the passes after it can't run out of stack either
+    let nested = |open: &str, inner: &str, close: &str, levels: usize| {
+        format!("{}{}{}", open.repeat(levels), inner, close.repeat(levels))
+    };
+    let deepest = nested("(", "1", ")", MAX_NESTING - 1);
+    assert_eq!(eval(&deepest), Ok(Value::Int(1)));
+    let longest = format!("1{}", " + 1".repeat(MAX_NESTING - 1));
+    assert_eq!(eval(&longest), Ok(Value::Int(MAX_NESTING as i32)));
+    for source in [
+        nested("(", "1", ")", 2000),
+        nested("{", "1", "}", 20000),
+        nested("-", "1", "", 50000),
+        nested("if true { 1 } else ", "{ 2 }", "", 20000),
+        nested("fn f() { ", "", "}", 20000),
+        format!("0{}", " + 0".repeat(5000)),
+        format!("true{}", " && true".repeat(5000)),
+    ] {
+        let error = eval(&source).unwrap_err();
+        assert_eq!(error.message, "expression nested too deeply");
+        assert!(!error.incomplete);
+    }
+    assert_eq!(
+        eval("fn add(a: i32, b: i32) -> i32 { a + b } add(1)")
+            .unwrap_err()
+            .message,
+        "this function takes 2 arguments but 1 argument was supplied"
+    );
+    assert_eq!(
+        eval("fn not(b: bool) -> bool { !b } not(1)")
+            .unwrap_err()
+            .message,
+        "mismatched types: expected `bool`, found `i32`"
+    );
+    assert_eq!(
+        eval("return 1").unwrap_err().message,
+        "return statement outside of function body"
+    );
+
+    // the error from plus_one_statement in main.rs, found before anything runs
+    let source = "fn plus_one_statement(x: i32) -> i32 {\n    x + 1;\n}";
+    let error = eval(source).unwrap_err();
+    assert_eq!(
+        error.render(source, "example"),
+        "error: mismatched types: expected `i32`, found `()`\n \
+         --> example:1:34\n  \
+         |\n\
+         1 | fn plus_one_statement(x: i32) -> i32 {\n  \
+         |                                  ^^^\n  \
+         = note: implicitly returns `()` as its body has no tail or `return` expression\n  \
+         = help: remove this semicolon to return this value"
+    );
+
+    // a program that fails to type check leaves nothing behind in the session
+    let mut session = Interpreter::new();
+    assert!(session.eval("let x = 1; let y = x + true;").is_err());
+    assert_eq!(
+        session.eval("x").unwrap_err().message,
+        "cannot find value `x` in this scope"
+    );
+    assert_eq!(
+        session.eval("fn double(n: i32) -> i32 { n * 2 }"),
+        Ok(Value::Unit)
+    );
+    assert_eq!(session.eval("double(21)"), Ok(Value::Int(42)));
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    I32,
+    Bool,
+    Unit,
+    // the type of `return`, which never produces a value and so fits wherever any other type is expected
+    Never,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::I32 => "i32",
+            Type::Bool => "bool",
+            Type::Unit => "()",
+            Type::Never => "!",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Eq => "==",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEq => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEq => ">=",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    Int(i32),
+    Bool(bool),
+    // `()`
+    Unit,
+    Var(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Block(Block),
+    If {
+        condition: Box<Expr>,
+        then_branch: Block,
+        // either a Block or another If, for `else if`
+        else_branch: Option<Box<Expr>>,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Return(Option<Box<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Let {
+        name: String,
+        name_span: Span,
+        value: Expr,
+    },
+    // an expression followed by `;`, or a block-like expression (`if`, `{}`) that is not the last one
+    Expr(Expr),
+    // functions are items: like in Rust they can be called anywhere in their block, even before the definition
+    Fn(Rc<FnDecl>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub ty: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnDecl {
+    pub name: String,
+    pub name_span: Span,
+    pub params: Vec<Param>,
+    pub return_type: Type,
+    // None when the `-> Type` part is left out and the function returns ()
+    pub return_span: Option<Span>,
+    pub body: Block,
+}
+
+// the body of a `{ }` block, or a whole program
+// its value is the value of `tail`, or `()` when the last thing in it is a statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub tail: Option<Box<Expr>>,
+    pub span: Span,
+}
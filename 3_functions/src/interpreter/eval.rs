@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, FnDecl, Stmt, UnaryOp};
+use super::typeck::Checker;
+use super::{Error, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Int(i32),
+    Bool(bool),
+    Unit,
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "i32",
+            Value::Bool(_) => "bool",
+            Value::Unit => "()",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
+
+// how many calls can be nested before the interpreter gives up, instead of overflowing its own stack:
+// every interpreted call is a handful of Rust calls, and a debug build runs out of the main thread's 8 MiB
+// somewhere below a thousand of them
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+// `return` unwinds through the blocks of a function until the call that started it, the same way errors do
+enum Unwind {
+    Error(Error),
+    Return(Value),
+}
+
+impl From<Error> for Unwind {
+    fn from(error: Error) -> Unwind {
+        Unwind::Error(error)
+    }
+}
+
+fn mismatched(expected: &str, found: Value, span: Span) -> Error {
+    Error::new(
+        format!(
+            "mismatched types: expected `{}`, found `{}`",
+            expected,
+            found.type_name()
+        ),
+        span,
+    )
+}
+
+// the bindings of the outermost scope survive between calls to `run`, which is what the REPL relies on
+pub struct Interpreter {
+    scopes: Vec<HashMap<String, Value>>,
+    functions: Vec<HashMap<String, Rc<FnDecl>>>,
+    depth: usize,
+    max_depth: usize,
+    checker: Checker,
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Interpreter::with_max_depth(DEFAULT_MAX_DEPTH)
+    }
+
+    pub fn with_max_depth(max_depth: usize) -> Interpreter {
+        Interpreter {
+            scopes: vec![HashMap::new()],
+            functions: vec![HashMap::new()],
+            depth: 0,
+            max_depth,
+            checker: Checker::new(),
+        }
+    }
+
+    // type checks the whole program before running any of it, like rustc refuses to build plus_one_statement
+    pub fn run(&mut self, program: &Block) -> Result<Value, Error> {
+        // a program that fails to check must not leave its bindings behind for the next one
+        let checker = self.checker.clone();
+        if let Err(error) = self.checker.check_program(program) {
+            self.checker = checker;
+            return Err(error);
+        }
+
+        match self.block_contents(program) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+            Err(Unwind::Return(_)) => {
+                unreachable!("the type checker rejects `return` outside of a function")
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Result<Value, Unwind> {
+        self.scopes.push(HashMap::new());
+        self.functions.push(HashMap::new());
+        let value = self.block_contents(block);
+        self.scopes.pop();
+        self.functions.pop();
+        value
+    }
+
+    fn block_contents(&mut self, block: &Block) -> Result<Value, Unwind> {
+        for stmt in &block.stmts {
+            if let Stmt::Fn(function) = stmt {
+                self.functions
+                    .last_mut()
+                    .expect("there is always a scope")
+                    .insert(function.name.clone(), Rc::clone(function));
+            }
+        }
+
+        for stmt in &block.stmts {
+            match stmt {
+                Stmt::Let { name, value, .. } => {
+                    let value = self.expr(value)?;
+                    self.scopes
+                        .last_mut()
+                        .expect("there is always a scope")
+                        .insert(name.clone(), value);
+                }
+                Stmt::Expr(expr) => {
+                    self.expr(expr)?;
+                }
+                Stmt::Fn(_) => {}
+            }
+        }
+        match &block.tail {
+            Some(tail) => self.expr(tail),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr], span: Span) -> Result<Value, Unwind> {
+        let function = self
+            .functions
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .ok_or_else(|| {
+                Error::new(
+                    format!("cannot find function `{}` in this scope", name),
+                    span,
+                )
+            })?;
+        if args.len() != function.params.len() {
+            return Err(Error::new(
+                format!(
+                    "this function takes {} arguments but {} were supplied",
+                    function.params.len(),
+                    args.len()
+                ),
+                span,
+            )
+            .into());
+        }
+        if self.depth >= self.max_depth {
+            return Err(Error::new(
+                format!(
+                    "reached the recursion limit while calling `{}` ({} nested calls)",
+                    name, self.max_depth
+                ),
+                span,
+            )
+            .into());
+        }
+
+        // arguments are evaluated in the caller's scope, before the callee's scope replaces it
+        let mut locals = HashMap::new();
+        for (param, arg) in function.params.iter().zip(args) {
+            locals.insert(param.name.clone(), self.expr(arg)?);
+        }
+
+        let caller = std::mem::replace(&mut self.scopes, vec![locals]);
+        self.depth += 1;
+        let result = self.block(&function.body);
+        self.depth -= 1;
+        self.scopes = caller;
+
+        match result {
+            Ok(value) | Err(Unwind::Return(value)) => Ok(value),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Value, Unwind> {
+        match &expr.kind {
+            ExprKind::Int(value) => Ok(Value::Int(*value)),
+            ExprKind::Bool(value) => Ok(Value::Bool(*value)),
+            ExprKind::Unit => Ok(Value::Unit),
+            ExprKind::Var(name) => Ok(self.lookup(name).ok_or_else(|| {
+                Error::new(
+                    format!("cannot find value `{}` in this scope", name),
+                    expr.span,
+                )
+            })?),
+            ExprKind::Unary(op, operand) => {
+                let value = self.expr(operand)?;
+                let result = match (op, value) {
+                    (UnaryOp::Neg, Value::Int(n)) => n
+                        .checked_neg()
+                        .map(Value::Int)
+                        .ok_or_else(|| Error::new("attempt to negate with overflow", expr.span)),
+                    (UnaryOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+                    (UnaryOp::Neg, other) => Err(mismatched("i32", other, operand.span)),
+                    (UnaryOp::Not, other) => Err(mismatched("bool", other, operand.span)),
+                };
+                Ok(result?)
+            }
+            ExprKind::Binary(op, left, right) => self.binary(*op, left, right, expr.span),
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => match self.expr(condition)? {
+                Value::Bool(true) => self.block(then_branch),
+                Value::Bool(false) => match else_branch {
+                    Some(branch) => self.expr(branch),
+                    None => Ok(Value::Unit),
+                },
+                other => Err(mismatched("bool", other, condition.span).into()),
+            },
+            ExprKind::Call { name, args } => self.call(name, args, expr.span),
+            ExprKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.expr(value)?,
+                    None => Value::Unit,
+                };
+                Err(Unwind::Return(value))
+            }
+        }
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOp,
+        left: &Expr,
+        right: &Expr,
+        span: Span,
+    ) -> Result<Value, Unwind> {
+        let lhs = self.expr(left)?;
+
+        // && and || only evaluate the right side when they need to
+        if let (BinaryOp::And | BinaryOp::Or, Value::Bool(l)) = (op, lhs) {
+            if (op == BinaryOp::And) != l {
+                return Ok(Value::Bool(l));
+            }
+            return match self.expr(right)? {
+                Value::Bool(r) => Ok(Value::Bool(r)),
+                other => Err(mismatched("bool", other, right.span).into()),
+            };
+        }
+
+        let rhs = self.expr(right)?;
+        let overflow = |name: &str| Error::new(format!("attempt to {} with overflow", name), span);
+
+        let result = match (lhs, rhs) {
+            (Value::Int(l), Value::Int(r)) => match op {
+                BinaryOp::Add => l
+                    .checked_add(r)
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow("add")),
+                BinaryOp::Sub => l
+                    .checked_sub(r)
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow("subtract")),
+                BinaryOp::Mul => l
+                    .checked_mul(r)
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow("multiply")),
+                BinaryOp::Div | BinaryOp::Rem if r == 0 => {
+                    let what = if op == BinaryOp::Div {
+                        "attempt to divide by zero"
+                    } else {
+                        "attempt to calculate the remainder with a divisor of zero"
+                    };
+                    Err(Error::new(what, right.span))
+                }
+                BinaryOp::Div => l
+                    .checked_div(r)
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow("divide")),
+                BinaryOp::Rem => l
+                    .checked_rem(r)
+                    .map(Value::Int)
+                    .ok_or_else(|| overflow("calculate the remainder")),
+                BinaryOp::Eq => Ok(Value::Bool(l == r)),
+                BinaryOp::NotEq => Ok(Value::Bool(l != r)),
+                BinaryOp::Less => Ok(Value::Bool(l < r)),
+                BinaryOp::LessEq => Ok(Value::Bool(l <= r)),
+                BinaryOp::Greater => Ok(Value::Bool(l > r)),
+                BinaryOp::GreaterEq => Ok(Value::Bool(l >= r)),
+                BinaryOp::And | BinaryOp::Or => Err(mismatched("bool", lhs, left.span)),
+            },
+            (Value::Bool(l), Value::Bool(r)) => match op {
+                BinaryOp::Eq => Ok(Value::Bool(l == r)),
+                BinaryOp::NotEq => Ok(Value::Bool(l != r)),
+                _ => Err(Error::new(
+                    format!("cannot apply `{}` to type `bool`", op.symbol()),
+                    span,
+                )),
+            },
+            (Value::Unit, Value::Unit) if matches!(op, BinaryOp::Eq | BinaryOp::NotEq) => {
+                Ok(Value::Bool(op == BinaryOp::Eq))
+            }
+            (lhs, rhs) if lhs.type_name() == rhs.type_name() => Err(Error::new(
+                format!(
+                    "cannot apply `{}` to type `{}`",
+                    op.symbol(),
+                    lhs.type_name()
+                ),
+                span,
+            )),
+            (lhs, rhs) => Err(mismatched(lhs.type_name(), rhs, right.span)),
+        };
+        Ok(result?)
+    }
+}
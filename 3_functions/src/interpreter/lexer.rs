@@ -0,0 +1,184 @@
+use super::{Error, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Int(i32),
+    Ident(String),
+    Let,
+    Fn,
+    Return,
+    If,
+    Else,
+    True,
+    False,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Bang,
+    Assign,
+    EqEq,
+    NotEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    AndAnd,
+    OrOr,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Semicolon,
+    Comma,
+    Colon,
+    Arrow,
+    Eof,
+}
+
+impl TokenKind {
+    // how the token is quoted in error messages, like rustc does
+    pub fn describe(&self) -> String {
+        let text = match self {
+            TokenKind::Int(n) => return format!("`{}`", n),
+            TokenKind::Ident(name) => return format!("`{}`", name),
+            TokenKind::Eof => return String::from("end of input"),
+            TokenKind::Let => "let",
+            TokenKind::Fn => "fn",
+            TokenKind::Return => "return",
+            TokenKind::If => "if",
+            TokenKind::Else => "else",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Plus => "+",
+            TokenKind::Minus => "-",
+            TokenKind::Star => "*",
+            TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
+            TokenKind::Bang => "!",
+            TokenKind::Assign => "=",
+            TokenKind::EqEq => "==",
+            TokenKind::NotEq => "!=",
+            TokenKind::Less => "<",
+            TokenKind::LessEq => "<=",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEq => ">=",
+            TokenKind::AndAnd => "&&",
+            TokenKind::OrOr => "||",
+            TokenKind::LParen => "(",
+            TokenKind::RParen => ")",
+            TokenKind::LBrace => "{",
+            TokenKind::RBrace => "}",
+            TokenKind::Semicolon => ";",
+            TokenKind::Comma => ",",
+            TokenKind::Colon => ":",
+            TokenKind::Arrow => "->",
+        };
+        format!("`{}`", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, Error> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+
+        let kind = if c.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let digits = source[start..i].replace('_', "");
+            let value = digits.parse().map_err(|_| {
+                Error::new(
+                    "integer literal is too large for `i32`",
+                    Span::new(start, i),
+                )
+            })?;
+            TokenKind::Int(value)
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            match &source[start..i] {
+                "let" => TokenKind::Let,
+                "fn" => TokenKind::Fn,
+                "return" => TokenKind::Return,
+                "if" => TokenKind::If,
+                "else" => TokenKind::Else,
+                "true" => TokenKind::True,
+                "false" => TokenKind::False,
+                name => TokenKind::Ident(name.to_string()),
+            }
+        } else {
+            let next = bytes.get(i + 1).copied();
+            let (kind, length) = match (c, next) {
+                (b'=', Some(b'=')) => (TokenKind::EqEq, 2),
+                (b'!', Some(b'=')) => (TokenKind::NotEq, 2),
+                (b'<', Some(b'=')) => (TokenKind::LessEq, 2),
+                (b'>', Some(b'=')) => (TokenKind::GreaterEq, 2),
+                (b'&', Some(b'&')) => (TokenKind::AndAnd, 2),
+                (b'|', Some(b'|')) => (TokenKind::OrOr, 2),
+                (b'-', Some(b'>')) => (TokenKind::Arrow, 2),
+                (b'+', _) => (TokenKind::Plus, 1),
+                (b'-', _) => (TokenKind::Minus, 1),
+                (b'*', _) => (TokenKind::Star, 1),
+                (b'/', _) => (TokenKind::Slash, 1),
+                (b'%', _) => (TokenKind::Percent, 1),
+                (b'!', _) => (TokenKind::Bang, 1),
+                (b'=', _) => (TokenKind::Assign, 1),
+                (b'<', _) => (TokenKind::Less, 1),
+                (b'>', _) => (TokenKind::Greater, 1),
+                (b'(', _) => (TokenKind::LParen, 1),
+                (b')', _) => (TokenKind::RParen, 1),
+                (b'{', _) => (TokenKind::LBrace, 1),
+                (b'}', _) => (TokenKind::RBrace, 1),
+                (b';', _) => (TokenKind::Semicolon, 1),
+                (b',', _) => (TokenKind::Comma, 1),
+                (b':', _) => (TokenKind::Colon, 1),
+                _ => {
+                    let width = source[start..].chars().next().map_or(1, char::len_utf8);
+                    let unknown = &source[start..start + width];
+                    return Err(Error::new(
+                        format!("unknown start of token: {}", unknown),
+                        Span::new(start, start + width),
+                    ));
+                }
+            };
+            i += length;
+            kind
+        };
+
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, i),
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(source.len(), source.len()),
+    });
+    Ok(tokens)
+}
@@ -0,0 +1,440 @@
+use std::rc::Rc;
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, FnDecl, Param, Stmt, Type, UnaryOp};
+use super::lexer::{Token, TokenKind};
+use super::{Error, Span};
+
+// how deeply blocks, `if`s, parentheses, unary and binary operators may nest
+// the parser, type checker and interpreter all recurse once per level, so without a limit
+// `((((...))))` a few thousand deep overflows the stack instead of reporting an error
+// `0 + 0 + ... + 0` nests too: it parses as `((0 + 0) + ...) + 0`, one level per operator
+pub const MAX_NESTING: usize = 256;
+
+pub struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    // how many of the nesting constructs above the current token is inside
+    depth: usize,
+}
+
+// binding power of each binary operator, higher binds tighter
+fn precedence(kind: &TokenKind) -> Option<(u8, BinaryOp)> {
+    let entry = match kind {
+        TokenKind::OrOr => (1, BinaryOp::Or),
+        TokenKind::AndAnd => (2, BinaryOp::And),
+        TokenKind::EqEq => (3, BinaryOp::Eq),
+        TokenKind::NotEq => (3, BinaryOp::NotEq),
+        TokenKind::Less => (3, BinaryOp::Less),
+        TokenKind::LessEq => (3, BinaryOp::LessEq),
+        TokenKind::Greater => (3, BinaryOp::Greater),
+        TokenKind::GreaterEq => (3, BinaryOp::GreaterEq),
+        TokenKind::Plus => (4, BinaryOp::Add),
+        TokenKind::Minus => (4, BinaryOp::Sub),
+        TokenKind::Star => (5, BinaryOp::Mul),
+        TokenKind::Slash => (5, BinaryOp::Div),
+        TokenKind::Percent => (5, BinaryOp::Rem),
+        _ => return None,
+    };
+    Some(entry)
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Parser {
+        Parser {
+            tokens,
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    // goes one nesting level deeper, or fails at the token that would go past MAX_NESTING
+    fn deeper(&mut self) -> Result<(), Error> {
+        if self.depth >= MAX_NESTING {
+            return Err(
+                Error::new("expression nested too deeply", self.peek().span).with_note(format!(
+                    "at most {} levels of nesting are allowed",
+                    MAX_NESTING
+                )),
+            );
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    // runs parse one nesting level deeper
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Parser) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        self.deeper()?;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::Eof {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn at(&self, kind: &TokenKind) -> bool {
+        &self.peek().kind == kind
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        let token = self.peek();
+        let error = Error::new(
+            format!("expected {}, found {}", expected, token.kind.describe()),
+            token.span,
+        );
+        if token.kind == TokenKind::Eof {
+            error.incomplete()
+        } else {
+            error
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<Token, Error> {
+        if self.at(&kind) {
+            Ok(self.next())
+        } else {
+            Err(self.unexpected(&kind.describe()))
+        }
+    }
+
+    // a program is the inside of a block without the braces
+    pub fn program(&mut self) -> Result<Block, Error> {
+        let start = self.peek().span.start;
+        let block = self.block_body(start, &TokenKind::Eof)?;
+        self.expect(TokenKind::Eof)?;
+        Ok(block)
+    }
+
+    fn block(&mut self) -> Result<Block, Error> {
+        self.nested(Parser::block_inner)
+    }
+
+    fn block_inner(&mut self) -> Result<Block, Error> {
+        let open = self.expect(TokenKind::LBrace)?;
+        let mut block = self.block_body(open.span.start, &TokenKind::RBrace)?;
+        let close = self.expect(TokenKind::RBrace)?;
+        block.span = open.span.to(close.span);
+        Ok(block)
+    }
+
+    fn block_body(&mut self, start: usize, end: &TokenKind) -> Result<Block, Error> {
+        let mut stmts = Vec::new();
+        let mut tail = None;
+
+        while !self.at(end) && !self.at(&TokenKind::Eof) {
+            if self.at(&TokenKind::Semicolon) {
+                self.next();
+                continue;
+            }
+            if self.at(&TokenKind::Let) {
+                stmts.push(self.let_statement()?);
+                continue;
+            }
+            if self.at(&TokenKind::Fn) {
+                stmts.push(Stmt::Fn(Rc::new(self.function()?)));
+                continue;
+            }
+
+            let expr = self.expression()?;
+            if self.at(&TokenKind::Semicolon) {
+                self.next();
+                stmts.push(Stmt::Expr(expr));
+            } else if self.at(end) {
+                tail = Some(Box::new(expr));
+            } else if is_block_like(&expr) {
+                // like in Rust, `if` and blocks end a statement without a semicolon
+                stmts.push(Stmt::Expr(expr));
+            } else {
+                return Err(self.unexpected("`;` or the end of the block"));
+            }
+        }
+
+        let end = self.peek().span.end;
+        Ok(Block {
+            stmts,
+            tail,
+            span: Span::new(start, end),
+        })
+    }
+
+    fn identifier(&mut self) -> Result<(String, Span), Error> {
+        if let TokenKind::Ident(name) = &self.peek().kind {
+            let name = name.clone();
+            let token = self.next();
+            Ok((name, token.span))
+        } else {
+            Err(self.unexpected("identifier"))
+        }
+    }
+
+    fn let_statement(&mut self) -> Result<Stmt, Error> {
+        self.expect(TokenKind::Let)?;
+        let (name, name_span) = self.identifier()?;
+        self.expect(TokenKind::Assign)?;
+        let value = self.expression()?;
+        self.expect(TokenKind::Semicolon)?;
+        Ok(Stmt::Let {
+            name,
+            name_span,
+            value,
+        })
+    }
+
+    // fn name(param: Type, ...) -> Type { body }
+    fn function(&mut self) -> Result<FnDecl, Error> {
+        self.expect(TokenKind::Fn)?;
+        let (name, name_span) = self.identifier()?;
+        self.expect(TokenKind::LParen)?;
+
+        let mut params = Vec::new();
+        while !self.at(&TokenKind::RParen) {
+            let (param, span) = self.identifier()?;
+            self.expect(TokenKind::Colon)?;
+            let (ty, _) = self.type_annotation()?;
+            params.push(Param {
+                name: param,
+                ty,
+                span,
+            });
+            if !self.at(&TokenKind::RParen) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        self.expect(TokenKind::RParen)?;
+
+        let (return_type, return_span) = if self.at(&TokenKind::Arrow) {
+            self.next();
+            let (ty, span) = self.type_annotation()?;
+            (ty, Some(span))
+        } else {
+            (Type::Unit, None)
+        };
+        let body = self.block()?;
+
+        Ok(FnDecl {
+            name,
+            name_span,
+            params,
+            return_type,
+            return_span,
+            body,
+        })
+    }
+
+    fn type_annotation(&mut self) -> Result<(Type, Span), Error> {
+        let token = self.peek().clone();
+        match &token.kind {
+            TokenKind::Ident(name) => {
+                self.next();
+                match name.as_str() {
+                    "i32" => Ok((Type::I32, token.span)),
+                    "bool" => Ok((Type::Bool, token.span)),
+                    _ => Err(Error::new(
+                        format!("cannot find type `{}` in this scope", name),
+                        token.span,
+                    )),
+                }
+            }
+            TokenKind::LParen => {
+                self.next();
+                let close = self.expect(TokenKind::RParen)?;
+                Ok((Type::Unit, token.span.to(close.span)))
+            }
+            _ => Err(self.unexpected("type")),
+        }
+    }
+
+    pub fn expression(&mut self) -> Result<Expr, Error> {
+        self.binary(0)
+    }
+
+    // precedence climbing: parse operands, then keep folding in operators that bind tighter than `min`
+    fn binary(&mut self, min: u8) -> Result<Expr, Error> {
+        // every operator folded in puts everything parsed so far one level deeper
+        let depth = self.depth;
+        let result = self.binary_inner(min);
+        self.depth = depth;
+        result
+    }
+
+    fn binary_inner(&mut self, min: u8) -> Result<Expr, Error> {
+        let mut left = self.unary()?;
+
+        while let Some((power, op)) = precedence(&self.peek().kind) {
+            if power <= min {
+                break;
+            }
+            self.deeper()?;
+            self.next();
+            let right = self.binary(power)?;
+            let span = left.span.to(right.span);
+            left = Expr {
+                kind: ExprKind::Binary(op, Box::new(left), Box::new(right)),
+                span,
+            };
+        }
+
+        Ok(left)
+    }
+
+    // every operand goes through here, so this counts parentheses as well as `-` and `!`
+    fn unary(&mut self) -> Result<Expr, Error> {
+        self.nested(Parser::unary_inner)
+    }
+
+    fn unary_inner(&mut self) -> Result<Expr, Error> {
+        let op = match self.peek().kind {
+            TokenKind::Minus => UnaryOp::Neg,
+            TokenKind::Bang => UnaryOp::Not,
+            _ => return self.primary(),
+        };
+        let token = self.next();
+        let operand = self.unary()?;
+        let span = token.span.to(operand.span);
+        Ok(Expr {
+            kind: ExprKind::Unary(op, Box::new(operand)),
+            span,
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr, Error> {
+        let token = self.peek().clone();
+        let kind = match token.kind {
+            TokenKind::Int(value) => {
+                self.next();
+                ExprKind::Int(value)
+            }
+            TokenKind::True | TokenKind::False => {
+                self.next();
+                ExprKind::Bool(token.kind == TokenKind::True)
+            }
+            TokenKind::Ident(ref name) => {
+                self.next();
+                if self.at(&TokenKind::LParen) {
+                    return self.call(name.clone(), token.span);
+                }
+                ExprKind::Var(name.clone())
+            }
+            TokenKind::Return => {
+                self.next();
+                let ends = [TokenKind::Semicolon, TokenKind::RBrace, TokenKind::Eof];
+                if ends.contains(&self.peek().kind) {
+                    ExprKind::Return(None)
+                } else {
+                    let value = self.expression()?;
+                    let span = token.span.to(value.span);
+                    return Ok(Expr {
+                        kind: ExprKind::Return(Some(Box::new(value))),
+                        span,
+                    });
+                }
+            }
+            TokenKind::LParen => {
+                self.next();
+                if self.at(&TokenKind::RParen) {
+                    let close = self.next();
+                    return Ok(Expr {
+                        kind: ExprKind::Unit,
+                        span: token.span.to(close.span),
+                    });
+                }
+                let inner = self.expression()?;
+                let close = self.expect(TokenKind::RParen)?;
+                return Ok(Expr {
+                    kind: inner.kind,
+                    span: token.span.to(close.span),
+                });
+            }
+            TokenKind::LBrace => {
+                let block = self.block()?;
+                return Ok(Expr {
+                    span: block.span,
+                    kind: ExprKind::Block(block),
+                });
+            }
+            TokenKind::If => return self.if_expression(),
+            // the error from the chapter: `let x = (let y = 6);`
+            TokenKind::Let => {
+                return Err(
+                    Error::new("expected expression, found statement (`let`)", token.span)
+                        .with_note("variable declaration using `let` is a statement"),
+                )
+            }
+            _ => return Err(self.unexpected("expression")),
+        };
+        Ok(Expr {
+            kind,
+            span: token.span,
+        })
+    }
+
+    fn call(&mut self, name: String, name_span: Span) -> Result<Expr, Error> {
+        self.expect(TokenKind::LParen)?;
+        let mut args = Vec::new();
+        while !self.at(&TokenKind::RParen) {
+            args.push(self.expression()?);
+            if !self.at(&TokenKind::RParen) {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+        let close = self.expect(TokenKind::RParen)?;
+        Ok(Expr {
+            kind: ExprKind::Call { name, args },
+            span: name_span.to(close.span),
+        })
+    }
+
+    // `else if` chains recurse here without going through unary
+    fn if_expression(&mut self) -> Result<Expr, Error> {
+        self.nested(Parser::if_inner)
+    }
+
+    fn if_inner(&mut self) -> Result<Expr, Error> {
+        let if_token = self.expect(TokenKind::If)?;
+        let condition = self.expression()?;
+        let then_branch = self.block()?;
+        let mut span = if_token.span.to(then_branch.span);
+
+        let else_branch = if self.at(&TokenKind::Else) {
+            self.next();
+            let branch = if self.at(&TokenKind::If) {
+                self.if_expression()?
+            } else {
+                let block = self.block()?;
+                Expr {
+                    span: block.span,
+                    kind: ExprKind::Block(block),
+                }
+            };
+            span = span.to(branch.span);
+            Some(Box::new(branch))
+        } else {
+            None
+        };
+
+        Ok(Expr {
+            kind: ExprKind::If {
+                condition: Box::new(condition),
+                then_branch,
+                else_branch,
+            },
+            span,
+        })
+    }
+}
+
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(expr.kind, ExprKind::Block(_) | ExprKind::If { .. })
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use super::ast::{BinaryOp, Block, Expr, ExprKind, FnDecl, Stmt, Type, UnaryOp};
+use super::{Error, Span};
+
+// checks the whole program before anything runs, so a function body with the wrong type is reported
+// even if the function is never called, like the commented out plus_one_statement in main.rs
+
+#[derive(Debug, Clone, PartialEq)]
+struct Signature {
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+// the outermost scope is kept between programs so that REPL inputs can use earlier bindings
+#[derive(Debug, Clone)]
+pub struct Checker {
+    variables: Vec<HashMap<String, Type>>,
+    functions: Vec<HashMap<String, Signature>>,
+    // the declared return type of each function being checked, innermost last
+    returns: Vec<Type>,
+}
+
+fn mismatched(expected: Type, found: Type, span: Span) -> Error {
+    Error::new(
+        format!(
+            "mismatched types: expected `{}`, found `{}`",
+            expected, found
+        ),
+        span,
+    )
+}
+
+// `!` fits anywhere, so `if x { return 1; } else { 2 }` has type i32
+fn coerces(found: Type, expected: Type) -> bool {
+    found == expected || found == Type::Never
+}
+
+impl Checker {
+    pub fn new() -> Checker {
+        Checker {
+            variables: vec![HashMap::new()],
+            functions: vec![HashMap::new()],
+            returns: Vec::new(),
+        }
+    }
+
+    pub fn check_program(&mut self, program: &Block) -> Result<Type, Error> {
+        self.block_contents(program)
+    }
+
+    fn block(&mut self, block: &Block) -> Result<Type, Error> {
+        self.variables.push(HashMap::new());
+        self.functions.push(HashMap::new());
+        let ty = self.block_contents(block);
+        self.variables.pop();
+        self.functions.pop();
+        ty
+    }
+
+    fn block_contents(&mut self, block: &Block) -> Result<Type, Error> {
+        // items first, so that functions can call each other (and themselves) in any order
+        for stmt in &block.stmts {
+            if let Stmt::Fn(function) = stmt {
+                let signature = Signature {
+                    params: function.params.iter().map(|p| p.ty).collect(),
+                    return_type: function.return_type,
+                };
+                self.functions
+                    .last_mut()
+                    .expect("there is always a scope")
+                    .insert(function.name.clone(), signature);
+            }
+        }
+
+        let mut diverges = false;
+        for stmt in &block.stmts {
+            match stmt {
+                Stmt::Let { name, value, .. } => {
+                    let ty = self.expr(value)?;
+                    diverges |= ty == Type::Never;
+                    self.variables
+                        .last_mut()
+                        .expect("there is always a scope")
+                        .insert(name.clone(), ty);
+                }
+                Stmt::Expr(expr) => {
+                    diverges |= self.expr(expr)? == Type::Never;
+                }
+                Stmt::Fn(function) => self.function(function)?,
+            }
+        }
+
+        match &block.tail {
+            Some(tail) => self.expr(tail),
+            None if diverges => Ok(Type::Never),
+            None => Ok(Type::Unit),
+        }
+    }
+
+    fn function(&mut self, function: &FnDecl) -> Result<(), Error> {
+        // a function body only sees its parameters, not the local variables around its definition
+        let outer = std::mem::take(&mut self.variables);
+        let params = function
+            .params
+            .iter()
+            .map(|p| (p.name.clone(), p.ty))
+            .collect();
+        self.variables = vec![params];
+        self.returns.push(function.return_type);
+        let body = self.block(&function.body);
+        self.returns.pop();
+        self.variables = outer;
+
+        let body = body?;
+        if coerces(body, function.return_type) {
+            return Ok(());
+        }
+
+        let expected = function.return_type;
+        match (&function.body.tail, function.return_span) {
+            (Some(tail), _) => Err(mismatched(expected, body, tail.span)),
+            (None, Some(return_span)) => {
+                let mut error = mismatched(expected, body, return_span).with_note(
+                    "implicitly returns `()` as its body has no tail or `return` expression",
+                );
+                // the mistake from plus_one_statement: `x + 1;` where `x + 1` was meant
+                if let Some(Stmt::Expr(last)) = function.body.stmts.last() {
+                    if self.expression_type_in(function, last) == Some(expected) {
+                        error = error.with_help("remove this semicolon to return this value");
+                    }
+                }
+                Err(error)
+            }
+            (None, None) => Err(mismatched(expected, body, function.name_span)),
+        }
+    }
+
+    // type of an expression that is the last statement of the function body, when it can be known again
+    fn expression_type_in(&mut self, function: &FnDecl, expr: &Expr) -> Option<Type> {
+        let outer = std::mem::take(&mut self.variables);
+        let mut scope: HashMap<String, Type> = function
+            .params
+            .iter()
+            .map(|p| (p.name.clone(), p.ty))
+            .collect();
+        for stmt in &function.body.stmts {
+            if let Stmt::Let { name, value, .. } = stmt {
+                self.variables = vec![scope.clone()];
+                if let Ok(ty) = self.expr(value) {
+                    scope.insert(name.clone(), ty);
+                }
+            }
+        }
+        self.variables = vec![scope];
+        self.returns.push(function.return_type);
+        let ty = self.expr(expr).ok();
+        self.returns.pop();
+        self.variables = outer;
+        ty
+    }
+
+    fn variable(&self, name: &str) -> Option<Type> {
+        self.variables
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn signature(&self, name: &str) -> Option<Signature> {
+        self.functions
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    fn expect(&mut self, expr: &Expr, expected: Type) -> Result<(), Error> {
+        let found = self.expr(expr)?;
+        if coerces(found, expected) {
+            Ok(())
+        } else {
+            Err(mismatched(expected, found, expr.span))
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Type, Error> {
+        match &expr.kind {
+            ExprKind::Int(_) => Ok(Type::I32),
+            ExprKind::Bool(_) => Ok(Type::Bool),
+            ExprKind::Unit => Ok(Type::Unit),
+            ExprKind::Var(name) => self.variable(name).ok_or_else(|| {
+                let mut error = Error::new(
+                    format!("cannot find value `{}` in this scope", name),
+                    expr.span,
+                );
+                if self.signature(name).is_some() {
+                    error = error.with_help(format!(
+                        "`{}` is a function, call it with `{}()`",
+                        name, name
+                    ));
+                }
+                error
+            }),
+            ExprKind::Unary(UnaryOp::Neg, operand) => {
+                self.expect(operand, Type::I32)?;
+                Ok(Type::I32)
+            }
+            ExprKind::Unary(UnaryOp::Not, operand) => {
+                self.expect(operand, Type::Bool)?;
+                Ok(Type::Bool)
+            }
+            ExprKind::Binary(op, left, right) => self.binary(*op, left, right, expr.span),
+            ExprKind::Block(block) => self.block(block),
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expect(condition, Type::Bool)?;
+                let then_type = self.block(then_branch)?;
+                match else_branch {
+                    None if coerces(then_type, Type::Unit) => Ok(Type::Unit),
+                    None => Err(Error::new(
+                        format!(
+                            "`if` may be missing an `else` clause: expected `()`, found `{}`",
+                            then_type
+                        ),
+                        expr.span,
+                    )),
+                    Some(branch) => {
+                        let else_type = self.expr(branch)?;
+                        if coerces(else_type, then_type) {
+                            Ok(then_type)
+                        } else if then_type == Type::Never {
+                            Ok(else_type)
+                        } else {
+                            Err(Error::new(
+                                format!(
+                                    "`if` and `else` have incompatible types: expected `{}`, found `{}`",
+                                    then_type, else_type
+                                ),
+                                branch.span,
+                            ))
+                        }
+                    }
+                }
+            }
+            ExprKind::Call { name, args } => {
+                let signature = self.signature(name).ok_or_else(|| {
+                    Error::new(
+                        format!("cannot find function `{}` in this scope", name),
+                        expr.span,
+                    )
+                })?;
+                if args.len() != signature.params.len() {
+                    let plural = |n: usize| if n == 1 { "" } else { "s" };
+                    return Err(Error::new(
+                        format!(
+                            "this function takes {} argument{} but {} argument{} supplied",
+                            signature.params.len(),
+                            plural(signature.params.len()),
+                            args.len(),
+                            if args.len() == 1 { " was" } else { "s were" }
+                        ),
+                        expr.span,
+                    ));
+                }
+                for (arg, param) in args.iter().zip(&signature.params) {
+                    self.expect(arg, *param)?;
+                }
+                Ok(signature.return_type)
+            }
+            ExprKind::Return(value) => {
+                let expected = *self.returns.last().ok_or_else(|| {
+                    Error::new("return statement outside of function body", expr.span)
+                })?;
+                match value {
+                    Some(value) => self.expect(value, expected)?,
+                    None if expected == Type::Unit => {}
+                    None => return Err(mismatched(expected, Type::Unit, expr.span)),
+                }
+                Ok(Type::Never)
+            }
+        }
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOp,
+        left: &Expr,
+        right: &Expr,
+        span: Span,
+    ) -> Result<Type, Error> {
+        let lhs = self.expr(left)?;
+        let rhs = self.expr(right)?;
+        let operand = match op {
+            BinaryOp::And | BinaryOp::Or => Some(Type::Bool),
+            BinaryOp::Eq | BinaryOp::NotEq => None,
+            _ => Some(Type::I32),
+        };
+        let result = match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+                Type::I32
+            }
+            _ => Type::Bool,
+        };
+
+        match operand {
+            Some(expected) if !coerces(lhs, expected) => Err(Error::new(
+                format!("cannot apply `{}` to type `{}`", op.symbol(), lhs),
+                span,
+            )),
+            Some(expected) if !coerces(rhs, expected) => Err(mismatched(expected, rhs, right.span)),
+            None if !coerces(rhs, lhs) && !coerces(lhs, rhs) => {
+                Err(mismatched(lhs, rhs, right.span))
+            }
+            _ => Ok(result),
+        }
+    }
+}
@@ -0,0 +1,3 @@
+// units.rs is a library module so that `cargo test` can run its examples,
+// including the compile_fail ones that show which quantities the compiler refuses to add
+pub mod units;
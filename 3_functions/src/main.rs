@@ -1,9 +1,33 @@
+mod increment;
+mod interpreter;
+
+use functions::units;
+
 fn main() {
+    // `cargo run -- repl` and `cargo run -- run <file>` start the interpreter from src/interpreter.rs instead
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("repl") => return interpreter::repl(),
+        Some("run") => {
+            let Some(path) = args.get(2) else {
+                eprintln!("usage: functions run <file>");
+                std::process::exit(2);
+            };
+            if let Err(error) = interpreter::run_file(path) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
     println!("Hello, world!");
 
     another_function();
     with_params(2);
     print_labeled_measurement(2, 'A');
+    print_labeled_measurement(5, 'm');
 
     // `Statements` are instructions that perform some action and do not return a value
     //
@@ -21,11 +45,17 @@ fn main() {
         x + 1
     };
     println!("The value of y is: {}", y);
+    // src/interpreter.rs evaluates the same kind of block expressions in a small language of its own
+    interpreter::interpreter();
 
     let x = five();
     println!("The value of x is: {}", x);
     let x = plus_one(x);
     println!("The value of x is: {}", x);
+    // src/increment.rs has five and plus_one for every integer type, with a choice of what happens at MAX
+    increment::increment();
+
+    units::units();
 }
 
 // rust code uses snake case as the conventional style for function and variable names
@@ -41,8 +71,12 @@ fn with_params(x: i32) {
 }
 
 // multiple params
+// the label is looked up in src/units.rs, so known units print with their proper symbol
 fn print_labeled_measurement(value: i32, unit_label: char) {
-    println!("The measurement is: {}{}", value, unit_label);
+    match units::Measurement::new(value as f64, unit_label.encode_utf8(&mut [0; 4])) {
+        Some(measurement) => println!("The measurement is: {}", measurement),
+        None => println!("The measurement is: {}{}", value, unit_label),
+    }
 }
 
 // functions can return values to the code that calls them
@@ -52,6 +86,7 @@ fn print_labeled_measurement(value: i32, unit_label: char) {
 fn five() -> i32 {
     5
 }
+// plus_one(i32::MAX) panics with "attempt to add with overflow" in a debug build, and returns i32::MIN in a release build
 fn plus_one(x: i32) -> i32 {
     x + 1
 }
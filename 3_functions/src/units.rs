@@ -0,0 +1,510 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// typed quantities for print_labeled_measurement
+// every dimension has its own unit enum, and a Quantity is generic over that enum,
+// so Length + Time is a type error while 1 km + 500 m converts the right side and adds them
+
+pub trait Unit: Copy + PartialEq + fmt::Debug + 'static {
+    const DIMENSION: &'static str;
+    const ALL: &'static [Self];
+
+    fn symbol(self) -> &'static str;
+
+    // extra spellings accepted when parsing, besides the symbol
+    fn aliases(self) -> &'static [&'static str] {
+        &[]
+    }
+
+    // to_base converts a value in this unit to the base unit of the dimension (metres, kilograms, ...)
+    // and base_to converts back
+    fn to_base(self, value: f64) -> f64;
+    fn base_to(self, value: f64) -> f64;
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|unit| unit.symbol() == symbol || unit.aliases().contains(&symbol))
+    }
+}
+
+// units whose conversion is a plain scale factor, which makes adding two quantities meaningful
+// temperature is not linear: 10 °C + 10 °C is not 20 °C once converted to kelvin and back
+pub trait LinearUnit: Unit {
+    // how many base units one of this unit is
+    fn factor(self) -> f64;
+}
+
+macro_rules! linear_unit {
+    ($unit:ident, $dimension:literal, $($variant:ident => ($symbol:literal, $factor:expr $(, $alias:literal)*)),+ $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $unit {
+            $($variant),+
+        }
+
+        impl Unit for $unit {
+            const DIMENSION: &'static str = $dimension;
+            const ALL: &'static [$unit] = &[$($unit::$variant),+];
+
+            fn symbol(self) -> &'static str {
+                match self {
+                    $($unit::$variant => $symbol),+
+                }
+            }
+
+            fn aliases(self) -> &'static [&'static str] {
+                match self {
+                    $($unit::$variant => &[$($alias),*]),+
+                }
+            }
+
+            fn to_base(self, value: f64) -> f64 {
+                value * self.factor()
+            }
+
+            fn base_to(self, value: f64) -> f64 {
+                value / self.factor()
+            }
+        }
+
+        impl LinearUnit for $unit {
+            fn factor(self) -> f64 {
+                match self {
+                    $($unit::$variant => $factor),+
+                }
+            }
+        }
+    };
+}
+
+linear_unit!(LengthUnit, "length",
+    Millimetre => ("mm", 0.001),
+    Centimetre => ("cm", 0.01),
+    Metre => ("m", 1.0),
+    Kilometre => ("km", 1000.0),
+    Inch => ("in", 0.0254, "\""),
+    Foot => ("ft", 0.3048, "'"),
+    Yard => ("yd", 0.9144),
+    Mile => ("mi", 1609.344),
+);
+
+linear_unit!(MassUnit, "mass",
+    Milligram => ("mg", 0.000_001),
+    Gram => ("g", 0.001),
+    Kilogram => ("kg", 1.0),
+    Tonne => ("t", 1000.0),
+    Ounce => ("oz", 0.028_349_523_125),
+    Pound => ("lb", 0.453_592_37, "lbs"),
+    Stone => ("st", 6.350_293_18),
+);
+
+linear_unit!(TimeUnit, "time",
+    Millisecond => ("ms", 0.001),
+    Second => ("s", 1.0, "sec"),
+    Minute => ("min", 60.0),
+    Hour => ("h", 3600.0, "hr"),
+    Day => ("d", 86_400.0),
+    Week => ("wk", 604_800.0),
+);
+
+// decimal prefixes are powers of 1000, binary ones (KiB, MiB) powers of 1024
+linear_unit!(DataUnit, "data size",
+    Bit => ("b", 0.125, "bit"),
+    Byte => ("B", 1.0),
+    Kilobyte => ("kB", 1e3, "KB"),
+    Megabyte => ("MB", 1e6),
+    Gigabyte => ("GB", 1e9),
+    Terabyte => ("TB", 1e12),
+    Kibibyte => ("KiB", 1024.0),
+    Mebibyte => ("MiB", 1_048_576.0),
+    Gibibyte => ("GiB", 1_073_741_824.0),
+    Tebibyte => ("TiB", 1_099_511_627_776.0),
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl Unit for TemperatureUnit {
+    const DIMENSION: &'static str = "temperature";
+    const ALL: &'static [TemperatureUnit] = &[
+        TemperatureUnit::Celsius,
+        TemperatureUnit::Fahrenheit,
+        TemperatureUnit::Kelvin,
+    ];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Kelvin => "K",
+        }
+    }
+
+    fn aliases(self) -> &'static [&'static str] {
+        match self {
+            TemperatureUnit::Celsius => &["C"],
+            TemperatureUnit::Fahrenheit => &["F"],
+            TemperatureUnit::Kelvin => &[],
+        }
+    }
+
+    fn to_base(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value + 273.15,
+            TemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0 + 273.15,
+            TemperatureUnit::Kelvin => value,
+        }
+    }
+
+    fn base_to(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value - 273.15,
+            TemperatureUnit::Fahrenheit => (value - 273.15) * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => value,
+        }
+    }
+}
+
+/// a value in one of the units of U's dimension
+///
+/// quantities of the same dimension add up, whatever their units:
+///
+/// ```
+/// # use functions::units::{Length, LengthUnit};
+/// let walk = Length::new(1.0, LengthUnit::Kilometre) + Length::new(500.0, LengthUnit::Metre);
+/// assert_eq!(walk, Length::new(1500.0, LengthUnit::Metre));
+/// ```
+///
+/// but metres and seconds are different types, so adding them doesn't compile:
+///
+/// ```compile_fail,E0308
+/// # use functions::units::{Length, LengthUnit, Time, TimeUnit};
+/// let _ = Length::new(1.0, LengthUnit::Metre) + Time::new(1.0, TimeUnit::Second); // error: mismatched types
+/// ```
+///
+/// temperatures convert but do not add, because their conversion has an offset:
+///
+/// ```
+/// # use functions::units::{Temperature, TemperatureUnit};
+/// let boiling = Temperature::new(100.0, TemperatureUnit::Celsius);
+/// assert!((boiling.value_in(TemperatureUnit::Fahrenheit) - 212.0).abs() < 1e-9);
+/// ```
+///
+/// ```compile_fail,E0369
+/// # use functions::units::{Temperature, TemperatureUnit};
+/// let boiling = Temperature::new(100.0, TemperatureUnit::Celsius);
+/// let _ = boiling + boiling; // error: cannot add `Quantity<TemperatureUnit>` to `Quantity<TemperatureUnit>`
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Quantity<U: Unit> {
+    value: f64,
+    unit: U,
+}
+
+pub type Length = Quantity<LengthUnit>;
+pub type Mass = Quantity<MassUnit>;
+pub type Time = Quantity<TimeUnit>;
+pub type Temperature = Quantity<TemperatureUnit>;
+pub type DataSize = Quantity<DataUnit>;
+
+impl<U: Unit> Quantity<U> {
+    pub fn new(value: f64, unit: U) -> Quantity<U> {
+        Quantity { value, unit }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn unit(&self) -> U {
+        self.unit
+    }
+
+    pub fn to(&self, unit: U) -> Quantity<U> {
+        Quantity::new(unit.base_to(self.unit.to_base(self.value)), unit)
+    }
+
+    pub fn value_in(&self, unit: U) -> f64 {
+        self.to(unit).value
+    }
+
+    pub fn approx_eq(&self, other: &Quantity<U>, tolerance: f64) -> bool {
+        (self.value_in(self.unit) - other.value_in(self.unit)).abs() <= tolerance
+    }
+
+    fn base(&self) -> f64 {
+        self.unit.to_base(self.value)
+    }
+}
+
+// quantities compare by their converted value, so 1 km == 1000 m
+impl<U: Unit> PartialEq for Quantity<U> {
+    fn eq(&self, other: &Quantity<U>) -> bool {
+        self.base() == other.base()
+    }
+}
+
+impl<U: Unit> PartialOrd for Quantity<U> {
+    fn partial_cmp(&self, other: &Quantity<U>) -> Option<Ordering> {
+        self.base().partial_cmp(&other.base())
+    }
+}
+
+// the result keeps the unit of the left hand side
+impl<U: LinearUnit> Add for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn add(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity::new(self.value + rhs.value_in(self.unit), self.unit)
+    }
+}
+
+impl<U: LinearUnit> Sub for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn sub(self, rhs: Quantity<U>) -> Quantity<U> {
+        Quantity::new(self.value - rhs.value_in(self.unit), self.unit)
+    }
+}
+
+impl<U: LinearUnit> Neg for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn neg(self) -> Quantity<U> {
+        Quantity::new(-self.value, self.unit)
+    }
+}
+
+impl<U: LinearUnit> Mul<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn mul(self, rhs: f64) -> Quantity<U> {
+        Quantity::new(self.value * rhs, self.unit)
+    }
+}
+
+impl<U: LinearUnit> Div<f64> for Quantity<U> {
+    type Output = Quantity<U>;
+
+    fn div(self, rhs: f64) -> Quantity<U> {
+        Quantity::new(self.value / rhs, self.unit)
+    }
+}
+
+// dividing two quantities of the same dimension gives a plain ratio
+impl<U: LinearUnit> Div for Quantity<U> {
+    type Output = f64;
+
+    fn div(self, rhs: Quantity<U>) -> f64 {
+        self.value / rhs.value_in(self.unit)
+    }
+}
+
+// "12.5 km"; a precision like {:.2} applies to the number
+impl<U: Unit> fmt::Display for Quantity<U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} {}", precision, self.value, self.unit.symbol()),
+            None => write!(f, "{} {}", self.value, self.unit.symbol()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseQuantityError {
+    InvalidNumber(String),
+    MissingUnit,
+    UnknownUnit {
+        dimension: &'static str,
+        symbol: String,
+    },
+}
+
+impl fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseQuantityError::InvalidNumber(number) => write!(f, "`{}` is not a number", number),
+            ParseQuantityError::MissingUnit => write!(f, "missing unit"),
+            ParseQuantityError::UnknownUnit { dimension, symbol } => {
+                write!(f, "`{}` is not a unit of {}", symbol, dimension)
+            }
+        }
+    }
+}
+
+impl Error for ParseQuantityError {}
+
+// splits "12.5 km" or "-40°F" into the number and the unit text
+fn split_number(input: &str) -> (&str, &str) {
+    let bytes = input.as_bytes();
+    let mut end = 0;
+    while end < bytes.len() {
+        let b = bytes[end];
+        let exponent = (b == b'e' || b == b'E')
+            && end > 0
+            && bytes
+                .get(end + 1)
+                .is_some_and(|n| n.is_ascii_digit() || *n == b'-' || *n == b'+');
+        let sign = (b == b'-' || b == b'+') && (end == 0 || matches!(bytes[end - 1], b'e' | b'E'));
+        if b.is_ascii_digit() || b == b'.' || b == b'_' || exponent || sign {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    (&input[..end], input[end..].trim())
+}
+
+impl<U: Unit> FromStr for Quantity<U> {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Quantity<U>, ParseQuantityError> {
+        let (number, symbol) = split_number(s.trim());
+        let value: f64 = number
+            .replace('_', "")
+            .parse()
+            .map_err(|_| ParseQuantityError::InvalidNumber(number.to_string()))?;
+        if symbol.is_empty() {
+            return Err(ParseQuantityError::MissingUnit);
+        }
+        let unit = U::from_symbol(symbol).ok_or_else(|| ParseQuantityError::UnknownUnit {
+            dimension: U::DIMENSION,
+            symbol: symbol.to_string(),
+        })?;
+        Ok(Quantity::new(value, unit))
+    }
+}
+
+// a quantity whose dimension is only known at runtime, for labels that come in as text or chars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Measurement {
+    Length(Length),
+    Mass(Mass),
+    Time(Time),
+    Temperature(Temperature),
+    Data(DataSize),
+}
+
+impl Measurement {
+    // the first dimension that knows the symbol wins, so "m" is a metre and not a minute
+    pub fn new(value: f64, symbol: &str) -> Option<Measurement> {
+        if let Some(unit) = LengthUnit::from_symbol(symbol) {
+            return Some(Measurement::Length(Quantity::new(value, unit)));
+        }
+        if let Some(unit) = MassUnit::from_symbol(symbol) {
+            return Some(Measurement::Mass(Quantity::new(value, unit)));
+        }
+        if let Some(unit) = TimeUnit::from_symbol(symbol) {
+            return Some(Measurement::Time(Quantity::new(value, unit)));
+        }
+        if let Some(unit) = TemperatureUnit::from_symbol(symbol) {
+            return Some(Measurement::Temperature(Quantity::new(value, unit)));
+        }
+        DataUnit::from_symbol(symbol).map(|unit| Measurement::Data(Quantity::new(value, unit)))
+    }
+
+    pub fn dimension(&self) -> &'static str {
+        match self {
+            Measurement::Length(_) => LengthUnit::DIMENSION,
+            Measurement::Mass(_) => MassUnit::DIMENSION,
+            Measurement::Time(_) => TimeUnit::DIMENSION,
+            Measurement::Temperature(_) => TemperatureUnit::DIMENSION,
+            Measurement::Data(_) => DataUnit::DIMENSION,
+        }
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Measurement::Length(q) => q.fmt(f),
+            Measurement::Mass(q) => q.fmt(f),
+            Measurement::Time(q) => q.fmt(f),
+            Measurement::Temperature(q) => q.fmt(f),
+            Measurement::Data(q) => q.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Measurement {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Measurement, ParseQuantityError> {
+        let (number, symbol) = split_number(s.trim());
+        let value: f64 = number
+            .replace('_', "")
+            .parse()
+            .map_err(|_| ParseQuantityError::InvalidNumber(number.to_string()))?;
+        if symbol.is_empty() {
+            return Err(ParseQuantityError::MissingUnit);
+        }
+        Measurement::new(value, symbol).ok_or_else(|| ParseQuantityError::UnknownUnit {
+            dimension: "any dimension",
+            symbol: symbol.to_string(),
+        })
+    }
+}
+
+pub fn units() {
+    let marathon: Length = "42.195 km".parse().unwrap();
+    assert!((marathon.value_in(LengthUnit::Mile) - 26.219).abs() < 0.001);
+    assert_eq!(format!("{:.1}", marathon.to(LengthUnit::Mile)), "26.2 mi");
+
+    let walk = Length::new(1.0, LengthUnit::Kilometre) + Length::new(500.0, LengthUnit::Metre);
+    assert_eq!(walk, Length::new(1500.0, LengthUnit::Metre));
+    assert_eq!(walk.to_string(), "1.5 km");
+    assert_eq!((walk.value(), walk.unit()), (1.5, LengthUnit::Kilometre));
+    assert_eq!(-walk + walk, Length::new(0.0, LengthUnit::Metre));
+    assert!(Length::new(1.0, LengthUnit::Mile) > Length::new(1.0, LengthUnit::Kilometre));
+    assert_eq!(walk / Length::new(500.0, LengthUnit::Metre), 3.0);
+    // the dimensions are checked by the compiler, see the compile_fail examples on Quantity
+
+    let weight = Mass::new(10.0, MassUnit::Stone);
+    assert!(weight.approx_eq(&Mass::new(140.0, MassUnit::Pound), 1e-9));
+    assert!(weight.approx_eq(&"63.5029318 kg".parse().unwrap(), 1e-9));
+
+    let shift = Time::new(3.0, TimeUnit::Hour) * 2.0 + "30 min".parse().unwrap();
+    assert_eq!(shift.value_in(TimeUnit::Minute), 390.0);
+
+    let boiling: Temperature = "100 °C".parse().unwrap();
+    assert!((boiling.value_in(TemperatureUnit::Fahrenheit) - 212.0).abs() < 1e-9);
+    let frozen: Temperature = "-40F".parse().unwrap();
+    assert!((frozen.value_in(TemperatureUnit::Celsius) + 40.0).abs() < 1e-9);
+    // temperatures convert but do not add, because their conversion has an offset (see Quantity)
+
+    let disk: DataSize = "1 GiB".parse().unwrap();
+    assert!((disk.value_in(DataUnit::Megabyte) - 1_073.741_824).abs() < 1e-9);
+    assert_eq!(
+        DataSize::new(1.0, DataUnit::Byte).value_in(DataUnit::Bit),
+        8.0
+    );
+
+    assert_eq!(
+        "12.5 parsecs".parse::<Length>(),
+        Err(ParseQuantityError::UnknownUnit {
+            dimension: "length",
+            symbol: String::from("parsecs"),
+        })
+    );
+    assert_eq!(
+        "km".parse::<Length>(),
+        Err(ParseQuantityError::InvalidNumber(String::new()))
+    );
+    assert_eq!("12".parse::<Length>(), Err(ParseQuantityError::MissingUnit));
+    assert_eq!(
+        "1.5e3 m".parse::<Length>(),
+        Ok(Length::new(1.5, LengthUnit::Kilometre))
+    );
+
+    let label: Measurement = "2 h".parse().unwrap();
+    assert_eq!(label.dimension(), "time");
+    assert_eq!(Measurement::new(2.0, "A"), None);
+}
@@ -0,0 +1,570 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+// the loops in main.rs and the guessing game in 0_guess_number are state machines that only exist in their
+// control flow: which branch runs next depends on where the loop is and what just happened
+// here the states and transitions are written down, so they can be checked and drawn
+//
+//     let machine = Machine::builder(Light::Off)
+//         .transition(Light::Off, Switch::Flip, Light::On)
+//         .transition(Light::On, Switch::Flip, Light::Off)
+//         .on_entry(Light::On, |count: &mut u32| *count += 1)
+//         .build(0)?;
+//
+// build() refuses machines with states that can't be reached from the initial one, and states that don't say
+// what happens for some event; `ignore` is how a state says an event does nothing
+
+type Guard<C> = Box<dyn Fn(&C) -> bool>;
+type Action<C> = Box<dyn FnMut(&mut C)>;
+
+struct Transition<S, E, C> {
+    from: S,
+    event: E,
+    to: S,
+    // the guard's description goes into error messages and DOT labels
+    guard: Option<(String, Guard<C>)>,
+    // an ignored event leaves the machine where it is, without running exit or entry actions
+    ignored: bool,
+}
+
+pub struct Builder<S, E, C> {
+    initial: S,
+    states: Vec<S>,
+    events: Vec<E>,
+    terminal: HashSet<S>,
+    transitions: Vec<Transition<S, E, C>>,
+    on_entry: HashMap<S, Action<C>>,
+    on_exit: HashMap<S, Action<C>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem<S, E> {
+    // no sequence of events leads from the initial state to this one
+    Unreachable(S),
+    // a state that isn't terminal and has no transition, guarded or not, for this event
+    MissingTransition { state: S, event: E },
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for Problem<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Unreachable(state) => write!(f, "state {:?} is unreachable", state),
+            Problem::MissingTransition { state, event } => {
+                write!(f, "state {:?} has no transition for {:?}", state, event)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError<S, E> {
+    pub problems: Vec<Problem<S, E>>,
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for ValidationError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problems: Vec<String> = self.problems.iter().map(|p| p.to_string()).collect();
+        write!(f, "invalid state machine: {}", problems.join("; "))
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for ValidationError<S, E> {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FireError<S, E> {
+    // the state has no transition for the event, which build() only lets through for terminal states
+    NoTransition {
+        state: S,
+        event: E,
+    },
+    // there are transitions for the event, but none of their guards passed
+    Rejected {
+        state: S,
+        event: E,
+        guards: Vec<String>,
+    },
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for FireError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FireError::NoTransition { state, event } => {
+                write!(f, "no transition from {:?} on {:?}", state, event)
+            }
+            FireError::Rejected {
+                state,
+                event,
+                guards,
+            } => write!(
+                f,
+                "no guard passed for {:?} in {:?}: {}",
+                event,
+                state,
+                guards.join(", ")
+            ),
+        }
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for FireError<S, E> {}
+
+impl<S, E, C> Builder<S, E, C>
+where
+    S: Copy + Eq + Hash + fmt::Debug,
+    E: Copy + Eq + Hash + fmt::Debug,
+{
+    fn add_state(&mut self, state: S) {
+        if !self.states.contains(&state) {
+            self.states.push(state);
+        }
+    }
+
+    fn add(mut self, transition: Transition<S, E, C>) -> Builder<S, E, C> {
+        self.add_state(transition.from);
+        self.add_state(transition.to);
+        if !self.events.contains(&transition.event) {
+            self.events.push(transition.event);
+        }
+        self.transitions.push(transition);
+        self
+    }
+
+    pub fn transition(self, from: S, event: E, to: S) -> Builder<S, E, C> {
+        self.add(Transition {
+            from,
+            event,
+            to,
+            guard: None,
+            ignored: false,
+        })
+    }
+
+    // guarded transitions for the same state and event are tried in the order they were added
+    pub fn guarded(
+        self,
+        from: S,
+        event: E,
+        to: S,
+        description: &str,
+        guard: impl Fn(&C) -> bool + 'static,
+    ) -> Builder<S, E, C> {
+        self.add(Transition {
+            from,
+            event,
+            to,
+            guard: Some((description.to_string(), Box::new(guard))),
+            ignored: false,
+        })
+    }
+
+    pub fn ignore(self, state: S, event: E) -> Builder<S, E, C> {
+        self.add(Transition {
+            from: state,
+            event,
+            to: state,
+            guard: None,
+            ignored: true,
+        })
+    }
+
+    // a state the machine is done in, which doesn't need transitions
+    pub fn terminal(mut self, state: S) -> Builder<S, E, C> {
+        self.add_state(state);
+        self.terminal.insert(state);
+        self
+    }
+
+    pub fn on_entry(mut self, state: S, action: impl FnMut(&mut C) + 'static) -> Builder<S, E, C> {
+        self.add_state(state);
+        self.on_entry.insert(state, Box::new(action));
+        self
+    }
+
+    pub fn on_exit(mut self, state: S, action: impl FnMut(&mut C) + 'static) -> Builder<S, E, C> {
+        self.add_state(state);
+        self.on_exit.insert(state, Box::new(action));
+        self
+    }
+
+    // unreachable states first, then missing transitions, each in the order the states were first mentioned
+    pub fn validate(&self) -> Vec<Problem<S, E>> {
+        let mut reachable = HashSet::from([self.initial]);
+        let mut queue = VecDeque::from([self.initial]);
+        while let Some(state) = queue.pop_front() {
+            for transition in self.transitions.iter().filter(|t| t.from == state) {
+                if reachable.insert(transition.to) {
+                    queue.push_back(transition.to);
+                }
+            }
+        }
+
+        let mut problems: Vec<Problem<S, E>> = self
+            .states
+            .iter()
+            .filter(|state| !reachable.contains(state))
+            .map(|&state| Problem::Unreachable(state))
+            .collect();
+        for &state in self.states.iter().filter(|s| !self.terminal.contains(s)) {
+            for &event in &self.events {
+                let handled = self
+                    .transitions
+                    .iter()
+                    .any(|t| t.from == state && t.event == event);
+                if !handled {
+                    problems.push(Problem::MissingTransition { state, event });
+                }
+            }
+        }
+        problems
+    }
+
+    // runs the entry action of the initial state, as if the machine had just moved into it
+    pub fn build(self, context: C) -> Result<Machine<S, E, C>, ValidationError<S, E>> {
+        let problems = self.validate();
+        if !problems.is_empty() {
+            return Err(ValidationError { problems });
+        }
+        let mut machine = Machine {
+            state: self.initial,
+            context,
+            definition: self,
+        };
+        if let Some(entry) = machine.definition.on_entry.get_mut(&machine.state) {
+            entry(&mut machine.context);
+        }
+        Ok(machine)
+    }
+}
+
+pub struct Machine<S, E, C> {
+    definition: Builder<S, E, C>,
+    state: S,
+    context: C,
+}
+
+impl<S, E, C> Machine<S, E, C>
+where
+    S: Copy + Eq + Hash + fmt::Debug,
+    E: Copy + Eq + Hash + fmt::Debug,
+{
+    pub fn builder(initial: S) -> Builder<S, E, C> {
+        Builder {
+            initial,
+            states: vec![initial],
+            events: Vec::new(),
+            terminal: HashSet::new(),
+            transitions: Vec::new(),
+            on_entry: HashMap::new(),
+            on_exit: HashMap::new(),
+        }
+    }
+
+    pub fn state(&self) -> S {
+        self.state
+    }
+
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.definition.terminal.contains(&self.state)
+    }
+
+    // runs the exit action of the old state and the entry action of the new one, and returns the new state
+    pub fn fire(&mut self, event: E) -> Result<S, FireError<S, E>> {
+        let state = self.state;
+        let candidates: Vec<&Transition<S, E, C>> = self
+            .definition
+            .transitions
+            .iter()
+            .filter(|t| t.from == state && t.event == event)
+            .collect();
+        let Some(transition) = candidates.iter().find(|t| {
+            t.guard
+                .as_ref()
+                .is_none_or(|(_, guard)| guard(&self.context))
+        }) else {
+            if candidates.is_empty() {
+                return Err(FireError::NoTransition { state, event });
+            }
+            let guards = candidates
+                .iter()
+                .filter_map(|t| t.guard.as_ref().map(|(description, _)| description.clone()))
+                .collect();
+            return Err(FireError::Rejected {
+                state,
+                event,
+                guards,
+            });
+        };
+        if transition.ignored {
+            return Ok(state);
+        }
+
+        let to = transition.to;
+        if let Some(exit) = self.definition.on_exit.get_mut(&state) {
+            exit(&mut self.context);
+        }
+        self.state = to;
+        if let Some(entry) = self.definition.on_entry.get_mut(&to) {
+            entry(&mut self.context);
+        }
+        Ok(to)
+    }
+
+    // the machine in Graphviz DOT, for `dot -Tsvg`
+    // terminal states are drawn with a double circle, ignored events are left out
+    // a state's Debug output like `Waiting { n: 1 }` isn't a valid DOT ID, so the states are called
+    // n0, n1, ... in the order they were first mentioned, with the Debug output as their label
+    pub fn to_dot(&self, name: &str) -> String {
+        let states = &self.definition.states;
+        let id = |state: &S| {
+            let index = states.iter().position(|s| s == state);
+            format!("n{}", index.expect("the builder records every state"))
+        };
+
+        let mut dot = format!("digraph {} {{\n    rankdir=LR;\n", quote(name));
+        dot.push_str("    start [shape=point];\n");
+        for state in states {
+            let shape = if self.definition.terminal.contains(state) {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            dot.push_str(&format!(
+                "    {} [label={}, shape={}];\n",
+                id(state),
+                quote(&format!("{:?}", state)),
+                shape
+            ));
+        }
+        dot.push_str(&format!("    start -> {};\n", id(&self.definition.initial)));
+        for transition in self.definition.transitions.iter().filter(|t| !t.ignored) {
+            let label = match &transition.guard {
+                Some((description, _)) => format!("{:?} [{}]", transition.event, description),
+                None => format!("{:?}", transition.event),
+            };
+            dot.push_str(&format!(
+                "    {} -> {} [label={}];\n",
+                id(&transition.from),
+                id(&transition.to),
+                quote(&label)
+            ));
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+// a DOT string: in double quotes, with `"` and `\` escaped
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// the guessing game from 0_guess_number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Game {
+    Guessing,
+    TooSmall,
+    TooBig,
+    Won,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Input {
+    Guess,
+    NotANumber,
+}
+
+struct Round {
+    secret: u32,
+    guess: u32,
+    output: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Lamp {
+    Off,
+    Blinking { times: u8 },
+}
+
+fn guessing_game(secret: u32) -> Result<Machine<Game, Input, Round>, ValidationError<Game, Input>> {
+    let mut builder = Machine::builder(Game::Guessing)
+        .terminal(Game::Won)
+        .on_entry(Game::Guessing, |round: &mut Round| {
+            round.output.push(String::from("Guess the number!"))
+        })
+        .on_entry(Game::TooSmall, |round| {
+            round.output.push(String::from("Too small!"))
+        })
+        .on_entry(Game::TooBig, |round| {
+            round.output.push(String::from("Too big!"))
+        })
+        .on_entry(Game::Won, |round| {
+            round.output.push(String::from("You win!"))
+        });
+    // the loop goes around from wherever the last guess left it
+    for state in [Game::Guessing, Game::TooSmall, Game::TooBig] {
+        builder = builder
+            .guarded(state, Input::Guess, Game::TooSmall, "guess < secret", |r| {
+                r.guess < r.secret
+            })
+            .guarded(state, Input::Guess, Game::TooBig, "guess > secret", |r| {
+                r.guess > r.secret
+            })
+            .guarded(state, Input::Guess, Game::Won, "guess == secret", |r| {
+                r.guess == r.secret
+            })
+            .ignore(state, Input::NotANumber);
+    }
+    builder.build(Round {
+        secret,
+        guess: 0,
+        output: Vec::new(),
+    })
+}
+
+// what the loop body in 0_guess_number does with one line of input
+fn play(
+    game: &mut Machine<Game, Input, Round>,
+    line: &str,
+) -> Result<Game, FireError<Game, Input>> {
+    match line.trim().parse() {
+        Ok(guess) => {
+            game.context_mut().guess = guess;
+            game.fire(Input::Guess)
+        }
+        Err(_) => {
+            let message = format!("{} is not a valid number", line.trim());
+            game.context_mut().output.push(message);
+            game.fire(Input::NotANumber)
+        }
+    }
+}
+
+pub fn fsm() {
+    let mut game = guessing_game(42).expect("the guessing game is a valid machine");
+    for line in ["50", "abc", "25", "42"] {
+        play(&mut game, line).unwrap();
+    }
+    assert_eq!(game.state(), Game::Won);
+    assert!(game.is_finished());
+    assert_eq!(
+        game.context().output,
+        [
+            "Guess the number!",
+            "Too big!",
+            "abc is not a valid number",
+            "Too small!",
+            "You win!"
+        ]
+    );
+    // the loop has ended, so there is nothing more to guess
+    assert_eq!(
+        play(&mut game, "7"),
+        Err(FireError::NoTransition {
+            state: Game::Won,
+            event: Input::Guess
+        })
+    );
+
+    // the same number twice in a row: TooBig is left and entered again, so it says "Too big!" twice
+    let mut game = guessing_game(10).unwrap();
+    assert_eq!(play(&mut game, "11"), Ok(Game::TooBig));
+    assert_eq!(play(&mut game, "11"), Ok(Game::TooBig));
+    assert_eq!(game.context().output[1..], ["Too big!", "Too big!"]);
+
+    let dot = game.to_dot("guessing_game");
+    assert!(dot.starts_with("digraph \"guessing_game\" {\n    rankdir=LR;\n"));
+    assert!(dot.contains("    start -> n0;\n"));
+    // Won is n1 because .terminal(Game::Won) mentions it before any transition does
+    assert!(dot.contains("    n1 [label=\"Won\", shape=doublecircle];\n"));
+    assert!(dot.contains("    n3 [label=\"TooBig\", shape=circle];\n"));
+    assert!(dot.contains("    n3 -> n1 [label=\"Guess [guess == secret]\"];\n"));
+    assert!(!dot.contains("NotANumber"));
+    println!("{}", dot);
+
+    // a counter whose guard can turn every transition down
+    let mut counter = Machine::builder("counting")
+        .guarded("counting", "add", "counting", "below 3", |n: &u32| *n < 3)
+        .transition("counting", "stop", "stopped")
+        .on_exit("counting", |n| *n += 1)
+        .terminal("stopped")
+        .build(0)
+        .unwrap();
+    for _ in 0..3 {
+        counter.fire("add").unwrap();
+    }
+    assert_eq!(*counter.context(), 3);
+    let error = counter.fire("add").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "no guard passed for \"add\" in \"counting\": below 3"
+    );
+    assert_eq!(counter.fire("stop"), Ok("stopped"));
+    assert_eq!(*counter.context(), 4);
+
+    // the quotes around &str states and events are escaped in the labels, as are the ones in the name
+    let dot = counter.to_dot("the \"counting\" machine");
+    assert!(dot.starts_with("digraph \"the \\\"counting\\\" machine\" {\n"));
+    assert!(dot.contains("    n0 [label=\"\\\"counting\\\"\", shape=circle];\n"));
+    assert!(dot.contains("    n0 -> n0 [label=\"\\\"add\\\" [below 3]\"];\n"));
+
+    // a state that carries data is only ever a label
+    let lamp = Machine::<_, _, ()>::builder(Lamp::Off)
+        .transition(Lamp::Off, "press", Lamp::Blinking { times: 3 })
+        .transition(Lamp::Blinking { times: 3 }, "press", Lamp::Off)
+        .build(())
+        .unwrap();
+    let dot = lamp.to_dot("lamp");
+    assert!(dot.contains("    n1 [label=\"Blinking { times: 3 }\", shape=circle];\n"));
+    assert!(dot.contains("    n1 -> n0 [label=\"\\\"press\\\"\"];\n"));
+
+    // "lost" has transitions out but none in, so it can never be reached from "idle",
+    // and "idle" and "running" each have no transition for one of the events
+    let broken = Machine::<_, _, ()>::builder("idle")
+        .transition("idle", "start", "running")
+        .transition("running", "stop", "idle")
+        .transition("lost", "start", "running")
+        .transition("lost", "stop", "idle")
+        .build(());
+    let error = broken.err().unwrap();
+    assert_eq!(
+        error.problems,
+        [
+            Problem::Unreachable("lost"),
+            Problem::MissingTransition {
+                state: "idle",
+                event: "stop"
+            },
+            Problem::MissingTransition {
+                state: "running",
+                event: "start"
+            },
+        ]
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid state machine: state \"lost\" is unreachable; \
+         state \"idle\" has no transition for \"stop\"; \
+         state \"running\" has no transition for \"start\""
+    );
+}
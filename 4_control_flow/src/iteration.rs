@@ -0,0 +1,269 @@
+use std::cell::Cell;
+use std::fmt;
+use std::ops::ControlFlow;
+use std::time::Duration;
+
+use crate::timer::{Clock, ManualClock};
+
+// `loop` with `break value` from main.rs, turned into functions
+// each step returns ControlFlow: Continue(()) to go around again, Break(value) to stop with a value
+
+// loops until the step breaks, and returns what it broke with
+pub fn repeat_until<T>(mut step: impl FnMut() -> ControlFlow<T>) -> T {
+    loop {
+        if let ControlFlow::Break(value) = step() {
+            break value;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub iterations: usize,
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "loop did not finish in {} iterations", self.iterations)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+// like repeat_until, but gives up after max_iterations; the step gets the 0-based iteration number
+pub fn bounded<T>(
+    max_iterations: usize,
+    mut step: impl FnMut(usize) -> ControlFlow<T>,
+) -> Result<T, LimitExceeded> {
+    for iteration in 0..max_iterations {
+        if let ControlFlow::Break(value) = step(iteration) {
+            return Ok(value);
+        }
+    }
+    Err(LimitExceeded {
+        iterations: max_iterations,
+    })
+}
+
+// applies f until two values in a row are close enough, and returns the last one with the number of steps taken
+pub fn fixed_point<T>(
+    initial: T,
+    max_iterations: usize,
+    mut f: impl FnMut(&T) -> T,
+    mut converged: impl FnMut(&T, &T) -> bool,
+) -> Result<(T, usize), LimitExceeded> {
+    let mut current = initial;
+    bounded(max_iterations, |iteration| {
+        let next = f(&current);
+        if converged(&current, &next) {
+            return ControlFlow::Break((next, iteration + 1));
+        }
+        current = next;
+        ControlFlow::Continue(())
+    })
+}
+
+// waits initial_delay after the first failure, and multiplier times longer after each one after that
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub multiplier: u32,
+    pub max_delay: Duration,
+}
+
+impl Backoff {
+    pub fn new(max_attempts: u32, initial_delay: Duration) -> Backoff {
+        Backoff {
+            max_attempts,
+            initial_delay,
+            multiplier: 2,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    pub fn multiplier(mut self, multiplier: u32) -> Backoff {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> Backoff {
+        self.max_delay = max_delay;
+        self
+    }
+
+    // the wait after the given failed attempt, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryError<E> {
+    pub attempts: u32,
+    // the error from the last attempt
+    pub last: E,
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gave up after {} attempts: {}", self.attempts, self.last)
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for RetryError<E> {}
+
+// calls operation with the attempt number until it succeeds or runs out of attempts, sleeping on the clock in between
+pub fn retry<T, E>(
+    clock: impl Clock,
+    backoff: Backoff,
+    mut operation: impl FnMut(u32) -> Result<T, E>,
+) -> Result<T, RetryError<E>> {
+    assert!(backoff.max_attempts > 0, "retry needs at least one attempt");
+    let mut attempt = 1;
+    loop {
+        match operation(attempt) {
+            Ok(value) => break Ok(value),
+            Err(last) if attempt == backoff.max_attempts => {
+                break Err(RetryError {
+                    attempts: attempt,
+                    last,
+                })
+            }
+            Err(_) => {
+                clock.sleep(backoff.delay(attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+pub fn iteration() {
+    // the loop from main.rs
+    let mut counter = 0;
+    let result = repeat_until(|| {
+        counter += 1;
+        if counter == 10 {
+            ControlFlow::Break(counter * 2)
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(result, 20);
+
+    // the `while number != 0` countdown, which would never end if it started below zero
+    let mut number = 3;
+    let mut shown = Vec::new();
+    let liftoff = bounded(10, |_| {
+        if number == 0 {
+            return ControlFlow::Break("LIFTOFF!!!");
+        }
+        shown.push(number);
+        number -= 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(liftoff, Ok("LIFTOFF!!!"));
+    assert_eq!(shown, [3, 2, 1]);
+    let mut number = -1;
+    let runaway = bounded(10, |_| {
+        if number == 0 {
+            return ControlFlow::Break(());
+        }
+        number -= 1;
+        ControlFlow::Continue(())
+    });
+    assert_eq!(runaway, Err(LimitExceeded { iterations: 10 }));
+    assert_eq!(
+        runaway.unwrap_err().to_string(),
+        "loop did not finish in 10 iterations"
+    );
+
+    // walking the array from main.rs by index, and finding the first element over 25
+    let a = [10, 20, 30, 40, 50];
+    let found = bounded(a.len(), |index| {
+        if a[index] > 25 {
+            ControlFlow::Break((index, a[index]))
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert_eq!(found, Ok((2, 30)));
+    assert!(bounded(a.len(), |index| if a[index] > 50 {
+        ControlFlow::Break(())
+    } else {
+        ControlFlow::Continue(())
+    })
+    .is_err());
+
+    // Newton's method for the square root of 2: x -> (x + 2 / x) / 2 stops moving at sqrt(2)
+    let close = |a: &f64, b: &f64| (a - b).abs() < 1e-12;
+    let (root, steps) = fixed_point(1.0, 100, |x| (x + 2.0 / x) / 2.0, close).unwrap();
+    assert!((root - 2f64.sqrt()).abs() < 1e-12);
+    assert!(steps < 10);
+    // cos has a fixed point too, it just takes a lot longer to get there
+    let (dottie, steps) = fixed_point(1.0f64, 1000, |x| x.cos(), close).unwrap();
+    assert!((dottie - 0.739_085_133_215_160_6).abs() < 1e-11);
+    assert!(steps > 50);
+    assert_eq!(
+        fixed_point(1.0f64, 20, |x| x.cos(), close),
+        Err(LimitExceeded { iterations: 20 })
+    );
+    // integers reach theirs exactly
+    assert_eq!(
+        fixed_point(
+            1_000_000u64,
+            100,
+            |x| (x + 1_000_000 / x) / 2,
+            |a, b| a == b
+        ),
+        Ok((1000, 13))
+    );
+
+    // three failures before it works, with 100ms, 200ms and 400ms in between
+    let clock = ManualClock::new();
+    let backoff = Backoff::new(5, Duration::from_millis(100));
+    let calls = Cell::new(0);
+    let result = retry(&clock, backoff, |attempt| {
+        calls.set(calls.get() + 1);
+        if attempt < 4 {
+            Err(format!("attempt {} failed", attempt))
+        } else {
+            Ok(attempt * 10)
+        }
+    });
+    assert_eq!(result, Ok(40));
+    assert_eq!(calls.get(), 4);
+    assert_eq!(clock.now(), Duration::from_millis(700));
+
+    // no sleeping after the last attempt
+    let clock = ManualClock::new();
+    let backoff = Backoff::new(3, Duration::from_secs(1)).multiplier(10);
+    let result: Result<(), _> = retry(&clock, backoff, Err);
+    assert_eq!(
+        result,
+        Err(RetryError {
+            attempts: 3,
+            last: 3
+        })
+    );
+    assert_eq!(clock.now(), Duration::from_secs(11));
+    assert_eq!(
+        RetryError {
+            attempts: 3,
+            last: "timed out"
+        }
+        .to_string(),
+        "gave up after 3 attempts: timed out"
+    );
+
+    let capped = Backoff::new(100, Duration::from_millis(500)).max_delay(Duration::from_secs(5));
+    let delays: Vec<u128> = (1..=6).map(|n| capped.delay(n).as_millis()).collect();
+    assert_eq!(delays, [500, 1000, 2000, 4000, 5000, 5000]);
+    assert_eq!(capped.delay(99), Duration::from_secs(5));
+}
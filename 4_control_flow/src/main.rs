@@ -1,4 +1,9 @@
+mod fsm;
+mod iteration;
 mod reference_and_borrowing;
+mod rules;
+mod sequence;
+mod timer;
 
 fn main() {
     // if-else
@@ -8,6 +13,8 @@ fn main() {
     } else {
         println!("condition was false");
     }
+    // src/rules.rs makes the same decision with the condition read from rules.toml
+    rules::rules();
 
     // if the condition isn’t a bool, we’ll get an error
     // let number = 3;
@@ -37,6 +44,8 @@ fn main() {
     // rust has three kinds of loops: loop, while, and for
 
     // loop keyword tells Rust to execute a block of code over and over again forever or until you explicitly tell it to stop
+    // clippy points out that this loop never repeats, which is the point of the example
+    #[allow(clippy::never_loop)]
     loop {
         println!("again!");
         // we need to call break to stop the loop
@@ -52,6 +61,8 @@ fn main() {
         }
     };
     println!("The result is {}", result);
+    // src/iteration.rs has this loop as a function, along with loops that give up after a number of iterations
+    iteration::iteration();
 
     // while
     let mut number = 3;
@@ -72,9 +83,18 @@ fn main() {
     for element in a {
         println!("the value is: {}", element);
     }
+    // src/reference_and_borrowing.rs loops over &a and &mut a as well, and shows what changes
+    reference_and_borrowing::reference_and_borrowing();
 
     // we can run for loop needed times with this expression:
     for number in (1..4).rev() {
         println!("{}!", number);
     }
+    // src/sequence.rs generates ranges like these, forwards, backwards or stepped, with FizzBuzz labels
+    sequence::sequence();
+    // both countdowns print as fast as they can, src/timer.rs waits an interval between the numbers
+    timer::timer();
+
+    // src/fsm.rs writes down the states a loop like the guessing game goes through
+    fsm::fsm();
 }
@@ -0,0 +1,133 @@
+// the `for element in a` loop in main.rs takes the array by value
+// a for loop can also borrow the collection, and what it borrows decides what the loop variable is:
+//
+//     for x in &a      x is &T       a is only read, and can be used again after the loop
+//     for x in &mut a  x is &mut T   the loop can change the elements in place
+//     for x in a       x is T        the loop owns the elements, and a is gone afterwards (unless it is Copy)
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub count: usize,
+    pub sum: i64,
+    pub min: i32,
+    pub max: i32,
+}
+
+// reads every value through a shared reference, None for an empty slice
+pub fn summarize(values: &[i32]) -> Option<Summary> {
+    let mut summary: Option<Summary> = None;
+    for &value in values {
+        let s = summary.get_or_insert(Summary {
+            count: 0,
+            sum: 0,
+            min: value,
+            max: value,
+        });
+        s.count += 1;
+        s.sum += i64::from(value);
+        s.min = s.min.min(value);
+        s.max = s.max.max(value);
+    }
+    summary
+}
+
+// changes every value in place through a mutable reference
+pub fn scale(values: &mut [i32], factor: i32) {
+    for value in values {
+        *value *= factor;
+    }
+}
+
+// both at once: the mutable borrow for the loop ends before the shared one for summarize starts
+pub fn clamp_and_summarize(values: &mut [i32], low: i32, high: i32) -> Option<Summary> {
+    for value in values.iter_mut() {
+        *value = (*value).clamp(low, high);
+    }
+    summarize(values)
+}
+
+// takes the strings by value, so the loop can move each one into the result without cloning
+pub fn join_owned(words: Vec<String>, separator: &str) -> String {
+    let mut joined = String::new();
+    for word in words {
+        if !joined.is_empty() {
+            joined.push_str(separator);
+        }
+        joined.push_str(&word);
+    }
+    joined
+}
+
+pub fn reference_and_borrowing() {
+    let mut a = [10, 20, 30, 40, 50];
+
+    // by shared reference: the elements are &i32, and a is still there afterwards
+    let mut total = 0;
+    // (the `let` with a type in each loop is how the compiler checks what the element is)
+    for element in &a {
+        let element: &i32 = element;
+        total += element;
+    }
+    assert_eq!(total, 150);
+    assert_eq!(a, [10, 20, 30, 40, 50]);
+
+    // by mutable reference: the elements are &mut i32, and writing through them changes a
+    for element in &mut a {
+        let element: &mut i32 = element;
+        *element += 1;
+    }
+    assert_eq!(a, [11, 21, 31, 41, 51]);
+
+    // by value: the elements are copies, so changing them leaves a alone
+    // an array of i32 is Copy, which is why main.rs can loop over `a` and still use it
+    for element in a {
+        let mut element: i32 = element;
+        element *= 100;
+        assert!(element > 1000);
+    }
+    assert_eq!(a, [11, 21, 31, 41, 51]);
+
+    // a Vec is not Copy, so looping over it by value moves it into the loop
+    let words = vec![String::from("hello"), String::from("world")];
+    for word in &words {
+        let word: &String = word;
+        assert!(!word.is_empty());
+    }
+    let joined = join_owned(words, ", ");
+    assert_eq!(joined, "hello, world");
+    // println!("{:?}", words); // error: borrow of moved value: `words`
+
+    // the same rules as for any other borrow: while the loop holds &mut a, nothing else can look at a
+    // for element in &mut a {
+    //     *element += a[0]; // error: cannot use `a[_]` because it was mutably borrowed
+    // }
+
+    let summary = summarize(&a).unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            count: 5,
+            sum: 155,
+            min: 11,
+            max: 51
+        }
+    );
+    assert_eq!(summarize(&[]), None);
+
+    scale(&mut a, 2);
+    assert_eq!(a, [22, 42, 62, 82, 102]);
+    // a part of the array can be borrowed on its own
+    scale(&mut a[..2], -1);
+    assert_eq!(a, [-22, -42, 62, 82, 102]);
+
+    let mut readings = vec![-5, 3, 120, 47];
+    let summary = clamp_and_summarize(&mut readings, 0, 100).unwrap();
+    assert_eq!(readings, [0, 3, 100, 47]);
+    assert_eq!(summary.sum, 150);
+    assert_eq!(summary.max, 100);
+
+    println!(
+        "{} values from {} to {}, {} in total",
+        summary.count, summary.min, summary.max, summary.sum
+    );
+}
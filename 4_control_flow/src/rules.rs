@@ -0,0 +1,541 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+// the if / else if / else chains from main.rs, with the conditions read from a TOML file instead of written in code
+//
+//     mode = "first"
+//
+//     [[rule]]
+//     name = "small"
+//     when = { op = "<", value = 5 }
+//     then = [{ print = "condition was true" }]
+//
+//     [[rule]]
+//     name = "otherwise"
+//     then = [{ print = "condition was false" }]
+//
+// a condition compares the input, or one field of it, with a value; `all`, `any` and `not` combine conditions
+// a rule without `when` always matches, which makes it the `else` at the end of a "first" rule set
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    // stop at the first rule that matches, like an if / else if chain
+    #[default]
+    First,
+    // run every rule that matches, like a row of separate ifs
+    All,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Str(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Int(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Str(s.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Op {
+    #[serde(rename = "==")]
+    Eq,
+    #[serde(rename = "!=")]
+    NotEq,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessEq,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterEq,
+}
+
+impl Op {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::NotEq => "!=",
+            Op::Less => "<",
+            Op::LessEq => "<=",
+            Op::Greater => ">",
+            Op::GreaterEq => ">=",
+        }
+    }
+
+    // None when the two values can't be compared, like a number with a string
+    // strings and bools only have == and !=, ordering is for numbers
+    fn apply(self, left: &Value, right: &Value) -> Option<bool> {
+        let ordering = match (left, right) {
+            (Value::Int(l), Value::Int(r)) => l.cmp(r),
+            (Value::Str(l), Value::Str(r)) if matches!(self, Op::Eq | Op::NotEq) => l.cmp(r),
+            (Value::Bool(l), Value::Bool(r)) if matches!(self, Op::Eq | Op::NotEq) => l.cmp(r),
+            _ => return None,
+        };
+        Some(match self {
+            Op::Eq => ordering.is_eq(),
+            Op::NotEq => ordering.is_ne(),
+            Op::Less => ordering.is_lt(),
+            Op::LessEq => ordering.is_le(),
+            Op::Greater => ordering.is_gt(),
+            Op::GreaterEq => ordering.is_ge(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum Condition {
+    All {
+        all: Vec<Condition>,
+    },
+    Any {
+        any: Vec<Condition>,
+    },
+    Not {
+        not: Box<Condition>,
+    },
+    Compare {
+        // left out when the input is a single number
+        field: Option<String>,
+        op: Op,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum Action {
+    Print(String),
+    Enable(String),
+    Disable(String),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Print(message) => write!(f, "print {:?}", message),
+            Action::Enable(feature) => write!(f, "enable {}", feature),
+            Action::Disable(feature) => write!(f, "disable {}", feature),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    pub when: Option<Condition>,
+    #[serde(default)]
+    pub then: Vec<Action>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Toml(toml::de::Error),
+    DuplicateRule(String),
+    EmptyCombinator(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Toml(error) => write!(f, "invalid rules: {}", error),
+            LoadError::DuplicateRule(name) => write!(f, "rule `{}` is defined twice", name),
+            LoadError::EmptyCombinator(name) => {
+                write!(
+                    f,
+                    "rule `{}` has an `all` or `any` with no conditions",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<toml::de::Error> for LoadError {
+    fn from(error: toml::de::Error) -> LoadError {
+        LoadError::Toml(error)
+    }
+}
+
+// what the rules are evaluated against: one number, or named fields
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    Number(i64),
+    Fields(BTreeMap<String, Value>),
+}
+
+impl Input {
+    pub fn fields<K: Into<String>, V: Into<Value>>(
+        fields: impl IntoIterator<Item = (K, V)>,
+    ) -> Input {
+        Input::Fields(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+
+    fn get(&self, field: Option<&str>) -> Option<Value> {
+        match (self, field) {
+            (Input::Number(n), None) => Some(Value::Int(*n)),
+            (Input::Fields(fields), Some(field)) => fields.get(field).cloned(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Matched,
+    NotMatched,
+    // in "first" mode, once a rule has matched the rest are not looked at
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub rule: String,
+    pub verdict: Verdict,
+    // the condition with the input values filled in, empty for skipped rules and rules without `when`
+    pub explanation: String,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verdict = match self.verdict {
+            Verdict::Matched => "matched",
+            Verdict::NotMatched => "did not match",
+            Verdict::Skipped => "skipped",
+        };
+        write!(f, "rule `{}` {}", self.rule, verdict)?;
+        if !self.explanation.is_empty() {
+            write!(f, ": {}", self.explanation)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome<'r> {
+    // the rules that matched, in order
+    pub fired: Vec<&'r Rule>,
+    pub trace: Vec<TraceEntry>,
+}
+
+impl<'r> Outcome<'r> {
+    pub fn actions(&self) -> impl Iterator<Item = &'r Action> + '_ {
+        self.fired.iter().flat_map(|rule| &rule.then)
+    }
+
+    pub fn fired_names(&self) -> Vec<&'r str> {
+        self.fired.iter().map(|rule| rule.name.as_str()).collect()
+    }
+
+    // features left enabled after applying every enable and disable in order
+    pub fn enabled_features(&self) -> Vec<&'r str> {
+        let mut enabled: Vec<&str> = Vec::new();
+        for action in self.actions() {
+            match action {
+                Action::Enable(feature) if !enabled.contains(&feature.as_str()) => {
+                    enabled.push(feature)
+                }
+                Action::Disable(feature) => enabled.retain(|f| f != feature),
+                _ => {}
+            }
+        }
+        enabled
+    }
+
+    pub fn messages(&self) -> Vec<&'r str> {
+        self.actions()
+            .filter_map(|action| match action {
+                Action::Print(message) => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Outcome<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, entry) in self.trace.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl Condition {
+    // evaluates every part, so the explanation shows the whole condition even after the answer is known
+    fn explain(&self, input: &Input) -> (bool, String) {
+        match self {
+            Condition::All { all } => {
+                let parts: Vec<_> = all.iter().map(|c| c.explain(input)).collect();
+                let result = parts.iter().all(|(matched, _)| *matched);
+                (result, combine("all", &parts))
+            }
+            Condition::Any { any } => {
+                let parts: Vec<_> = any.iter().map(|c| c.explain(input)).collect();
+                let result = parts.iter().any(|(matched, _)| *matched);
+                (result, combine("any", &parts))
+            }
+            Condition::Not { not } => {
+                let (matched, explanation) = not.explain(input);
+                (!matched, format!("not ({})", explanation))
+            }
+            Condition::Compare { field, op, value } => {
+                let name = field.as_deref().unwrap_or("input");
+                let comparison = format!("{} {} {}", name, op.symbol(), value);
+                match input.get(field.as_deref()) {
+                    None => (false, format!("{} [{} is missing]", comparison, name)),
+                    Some(actual) => match op.apply(&actual, value) {
+                        Some(result) => (
+                            result,
+                            format!("{} [{} = {}: {}]", comparison, name, actual, result),
+                        ),
+                        None => (
+                            false,
+                            format!("{} [{} = {}: cannot compare]", comparison, name, actual),
+                        ),
+                    },
+                }
+            }
+        }
+    }
+
+    fn has_empty_combinator(&self) -> bool {
+        match self {
+            Condition::All { all: parts } | Condition::Any { any: parts } => {
+                parts.is_empty() || parts.iter().any(Condition::has_empty_combinator)
+            }
+            Condition::Not { not } => not.has_empty_combinator(),
+            Condition::Compare { .. } => false,
+        }
+    }
+}
+
+fn combine(name: &str, parts: &[(bool, String)]) -> String {
+    let parts: Vec<&str> = parts.iter().map(|(_, text)| text.as_str()).collect();
+    format!("{} of ({})", name, parts.join(", "))
+}
+
+impl FromStr for RuleSet {
+    type Err = LoadError;
+
+    fn from_str(source: &str) -> Result<RuleSet, LoadError> {
+        let rules: RuleSet = toml::from_str(source)?;
+        let mut names = HashSet::new();
+        for rule in &rules.rules {
+            if !names.insert(rule.name.as_str()) {
+                return Err(LoadError::DuplicateRule(rule.name.clone()));
+            }
+            if rule
+                .when
+                .as_ref()
+                .is_some_and(Condition::has_empty_combinator)
+            {
+                return Err(LoadError::EmptyCombinator(rule.name.clone()));
+            }
+        }
+        Ok(rules)
+    }
+}
+
+impl RuleSet {
+    pub fn evaluate(&self, input: &Input) -> Outcome<'_> {
+        self.evaluate_with(self.mode, input)
+    }
+
+    pub fn evaluate_with(&self, mode: Mode, input: &Input) -> Outcome<'_> {
+        let mut outcome = Outcome {
+            fired: Vec::new(),
+            trace: Vec::new(),
+        };
+        for rule in &self.rules {
+            if mode == Mode::First && !outcome.fired.is_empty() {
+                outcome.trace.push(TraceEntry {
+                    rule: rule.name.clone(),
+                    verdict: Verdict::Skipped,
+                    explanation: String::new(),
+                });
+                continue;
+            }
+            let (matched, explanation) = match &rule.when {
+                Some(condition) => condition.explain(input),
+                None => (true, String::new()),
+            };
+            if matched {
+                outcome.fired.push(rule);
+            }
+            outcome.trace.push(TraceEntry {
+                rule: rule.name.clone(),
+                verdict: if matched {
+                    Verdict::Matched
+                } else {
+                    Verdict::NotMatched
+                },
+                explanation,
+            });
+        }
+        outcome
+    }
+}
+
+// the if / else examples from main.rs
+const RULES: &str = include_str!("../rules.toml");
+
+// the same kind of rules deciding which features a customer gets
+const FEATURES: &str = r#"
+mode = "all"
+
+[[rule]]
+name = "paid plan"
+when = { any = [{ field = "plan", op = "==", value = "pro" }, { field = "plan", op = "==", value = "team" }] }
+then = [{ enable = "export" }, { enable = "themes" }]
+
+[[rule]]
+name = "large team"
+when = { all = [{ field = "plan", op = "==", value = "team" }, { field = "seats", op = ">=", value = 10 }] }
+then = [{ enable = "audit-log" }]
+
+[[rule]]
+name = "beta testers"
+when = { field = "beta", op = "==", value = true }
+then = [{ enable = "new-editor" }]
+
+[[rule]]
+name = "suspended"
+when = { not = { field = "active", op = "==", value = true } }
+then = [{ disable = "export" }, { print = "account is suspended" }]
+"#;
+
+pub fn rules() {
+    let rules: RuleSet = RULES.parse().expect("rules.toml is valid");
+    assert_eq!(rules.mode, Mode::First);
+
+    let outcome = rules.evaluate(&Input::Number(3));
+    assert_eq!(outcome.messages(), ["condition was true"]);
+    assert_eq!(
+        outcome.to_string(),
+        "rule `less than five` matched: input < 5 [input = 3: true]\n\
+         rule `otherwise` skipped"
+    );
+    assert_eq!(
+        rules.evaluate(&Input::Number(7)).messages(),
+        ["condition was false"]
+    );
+    // in "all" mode the `else` rule always fires as well
+    assert_eq!(
+        rules
+            .evaluate_with(Mode::All, &Input::Number(3))
+            .fired_names(),
+        ["less than five", "otherwise"]
+    );
+    for line in rules.evaluate(&Input::Number(7)).trace {
+        println!("{}", line);
+    }
+
+    let features: RuleSet = FEATURES.parse().unwrap();
+    let customer = Input::fields([
+        ("plan", Value::from("team")),
+        ("seats", Value::from(12)),
+        ("beta", Value::from(false)),
+        ("active", Value::from(true)),
+    ]);
+    let outcome = features.evaluate(&customer);
+    assert_eq!(outcome.fired_names(), ["paid plan", "large team"]);
+    assert_eq!(
+        outcome.enabled_features(),
+        ["export", "themes", "audit-log"]
+    );
+    assert_eq!(
+        outcome.trace[1].to_string(),
+        "rule `large team` matched: all of (plan == \"team\" [plan = \"team\": true], \
+         seats >= 10 [seats = 12: true])"
+    );
+
+    // a missing field or a value of the wrong type makes a comparison false, and the trace says why
+    let outcome = features.evaluate(&Input::fields([("plan", 3), ("active", 1)]));
+    assert_eq!(outcome.fired_names(), ["suspended"]);
+    assert_eq!(outcome.messages(), ["account is suspended"]);
+    assert_eq!(
+        outcome.trace[2].explanation,
+        "beta == true [beta is missing]"
+    );
+    assert_eq!(
+        outcome.trace[3].explanation,
+        "not (active == true [active = 1: cannot compare])"
+    );
+    let outcome = features.evaluate(&Input::Number(5));
+    assert_eq!(
+        outcome.trace[2].explanation,
+        "beta == true [beta is missing]"
+    );
+
+    let duplicate = "[[rule]]\nname = \"a\"\n\n[[rule]]\nname = \"a\"\n";
+    assert_eq!(
+        duplicate.parse::<RuleSet>().unwrap_err().to_string(),
+        "rule `a` is defined twice"
+    );
+    let empty = "[[rule]]\nname = \"a\"\nwhen = { any = [] }\n";
+    assert_eq!(
+        empty.parse::<RuleSet>().unwrap_err().to_string(),
+        "rule `a` has an `all` or `any` with no conditions"
+    );
+    assert!(matches!(
+        "[[rule]]\nname = \"a\"\nwhen = { op = \"=<\", value = 5 }\n".parse::<RuleSet>(),
+        Err(LoadError::Toml(_))
+    ));
+    assert!(matches!(
+        "mode = \"sometimes\"".parse::<RuleSet>(),
+        Err(LoadError::Toml(_))
+    ));
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::iter::FusedIterator;
+use std::ops::RangeInclusive;
+
+// the range loops from main.rs with FizzBuzz on top: every number in the range is printed as itself,
+// or as the labels of the rules whose divisor it is a multiple of
+//
+//     Sequence::new(1..=15).rule(3, "Fizz").rule(5, "Buzz")
+//
+// numbers are only worked out when the iterator gets to them, so 0..=u64::MAX is as cheap as 1..=15
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub divisor: u64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequence {
+    start: u64,
+    end: u64,
+    step: u64,
+    reversed: bool,
+    rules: Vec<Rule>,
+}
+
+impl Sequence {
+    pub fn new(range: RangeInclusive<u64>) -> Sequence {
+        Sequence {
+            start: *range.start(),
+            end: *range.end(),
+            step: 1,
+            reversed: false,
+            rules: Vec::new(),
+        }
+    }
+
+    // the classic: multiples of 3 are Fizz, of 5 Buzz, and of both FizzBuzz
+    pub fn fizzbuzz(range: RangeInclusive<u64>) -> Sequence {
+        Sequence::new(range).rule(3, "Fizz").rule(5, "Buzz")
+    }
+
+    // labels are joined in the order the rules were added
+    pub fn rule(mut self, divisor: u64, label: &str) -> Sequence {
+        assert!(divisor != 0, "a rule's divisor must not be zero");
+        self.rules.push(Rule {
+            divisor,
+            label: label.to_string(),
+        });
+        self
+    }
+
+    // counts down from the end of the range, like (1..4).rev()
+    pub fn rev(mut self) -> Sequence {
+        self.reversed = !self.reversed;
+        self
+    }
+
+    // every step-th number, counting from whichever end the sequence starts at
+    pub fn step_by(mut self, step: u64) -> Sequence {
+        assert!(step != 0, "step must not be zero");
+        self.step = step;
+        self
+    }
+
+    pub fn iter(&self) -> Outputs<'_> {
+        let next = if self.start > self.end {
+            None
+        } else if self.reversed {
+            Some(self.end)
+        } else {
+            Some(self.start)
+        };
+        Outputs {
+            sequence: self,
+            next,
+        }
+    }
+
+    // writes one output per line, stopping at the first write error
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        for output in self {
+            writeln!(out, "{}", output)?;
+        }
+        Ok(())
+    }
+
+    // a closed pipe (like `| head`) just ends the output
+    pub fn print(&self) -> io::Result<()> {
+        let mut out = BufWriter::new(io::stdout().lock());
+        match self.write_to(&mut out).and_then(|()| out.flush()) {
+            Err(error) if error.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    }
+}
+
+// one number of the sequence; it is only turned into text when it is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Output<'a> {
+    pub number: u64,
+    rules: &'a [Rule],
+}
+
+impl<'a> Output<'a> {
+    pub fn labels(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.rules
+            .iter()
+            .filter(|rule| self.number.is_multiple_of(rule.divisor))
+            .map(|rule| rule.label.as_str())
+    }
+
+    pub fn is_labeled(&self) -> bool {
+        self.labels().next().is_some()
+    }
+}
+
+impl fmt::Display for Output<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_labeled() {
+            return write!(f, "{}", self.number);
+        }
+        for label in self.labels() {
+            f.write_str(label)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Outputs<'a> {
+    sequence: &'a Sequence,
+    next: Option<u64>,
+}
+
+impl<'a> Iterator for Outputs<'a> {
+    type Item = Output<'a>;
+
+    fn next(&mut self) -> Option<Output<'a>> {
+        let number = self.next?;
+        let sequence = self.sequence;
+        // checked arithmetic ends the sequence at the edges of u64 instead of wrapping around
+        self.next = if sequence.reversed {
+            number
+                .checked_sub(sequence.step)
+                .filter(|&n| n >= sequence.start)
+        } else {
+            number
+                .checked_add(sequence.step)
+                .filter(|&n| n <= sequence.end)
+        };
+        Some(Output {
+            number,
+            rules: &sequence.rules,
+        })
+    }
+}
+
+impl FusedIterator for Outputs<'_> {}
+
+impl<'a> IntoIterator for &'a Sequence {
+    type Item = Output<'a>;
+    type IntoIter = Outputs<'a>;
+
+    fn into_iter(self) -> Outputs<'a> {
+        self.iter()
+    }
+}
+
+// the way FizzBuzz is usually written in an interview, to check the generator against
+fn naive_fizzbuzz(n: u64) -> String {
+    if n.is_multiple_of(15) {
+        String::from("FizzBuzz")
+    } else if n.is_multiple_of(3) {
+        String::from("Fizz")
+    } else if n.is_multiple_of(5) {
+        String::from("Buzz")
+    } else {
+        n.to_string()
+    }
+}
+
+fn strings(sequence: &Sequence) -> Vec<String> {
+    sequence.iter().map(|output| output.to_string()).collect()
+}
+
+pub fn sequence() {
+    let fizzbuzz = Sequence::fizzbuzz(1..=15);
+    assert_eq!(
+        strings(&fizzbuzz),
+        [
+            "1", "2", "Fizz", "4", "Buzz", "Fizz", "7", "8", "Fizz", "Buzz", "11", "Fizz", "13",
+            "14", "FizzBuzz"
+        ]
+    );
+    let expected: Vec<String> = (1..=1000).map(naive_fizzbuzz).collect();
+    assert_eq!(strings(&Sequence::fizzbuzz(1..=1000)), expected);
+
+    // the countdown from main.rs, `for number in (1..4).rev()`
+    assert_eq!(strings(&Sequence::new(1..=3).rev()), ["3", "2", "1"]);
+    // stepping starts from whichever end comes first
+    assert_eq!(
+        strings(&Sequence::fizzbuzz(1..=20).step_by(4)),
+        ["1", "Buzz", "Fizz", "13", "17"]
+    );
+    assert_eq!(
+        strings(&Sequence::fizzbuzz(1..=20).rev().step_by(4)),
+        ["Buzz", "16", "Fizz", "8", "4"]
+    );
+    let (high, low) = (5, 1);
+    assert_eq!(Sequence::new(high..=low).iter().count(), 0);
+    assert_eq!(strings(&Sequence::new(7..=7)), ["7"]);
+
+    // any number of rules, joined in order; 0 is a multiple of everything
+    let rules = Sequence::new(0..=21)
+        .rule(2, "Foo")
+        .rule(3, "Bar")
+        .rule(7, "Baz");
+    let outputs: Vec<String> = rules.iter().step_by(7).map(|o| o.to_string()).collect();
+    assert_eq!(outputs, ["FooBarBaz", "Baz", "FooBaz", "BarBaz"]);
+    assert_eq!(strings(&Sequence::new(1..=3)), ["1", "2", "3"]);
+    let labels: Vec<&str> = rules.iter().nth(6).unwrap().labels().collect();
+    assert_eq!(labels, ["Foo", "Bar"]);
+
+    // the whole of u64, without ever holding more than one number
+    let everything = Sequence::fizzbuzz(0..=u64::MAX);
+    assert_eq!(
+        everything.iter().nth(1_000_000).unwrap().to_string(),
+        "Buzz"
+    );
+    let last: Vec<String> = everything
+        .rev()
+        .iter()
+        .take(3)
+        .map(|o| o.to_string())
+        .collect();
+    assert_eq!(
+        last,
+        ["FizzBuzz", "18446744073709551614", "18446744073709551613"]
+    );
+    // the end of the range is reached without overflowing
+    let top = Sequence::new(u64::MAX - 2..=u64::MAX);
+    assert_eq!(top.iter().count(), 3);
+    assert_eq!(top.step_by(u64::MAX).iter().count(), 1);
+    assert_eq!(
+        Sequence::new(0..=u64::MAX)
+            .step_by(u64::MAX / 2)
+            .iter()
+            .count(),
+        3
+    );
+
+    let mut out = Vec::new();
+    Sequence::fizzbuzz(9..=12).write_to(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "Fizz\nBuzz\n11\nFizz\n");
+
+    Sequence::fizzbuzz(1..=15)
+        .print()
+        .expect("Failed to write to stdout");
+}
@@ -0,0 +1,345 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// the countdown loops from main.rs print every number at once
+// a Timer spaces them out on a clock: a countdown ticks once per interval until it reaches zero,
+// an interval timer ticks once per interval until it is stopped, and both can be paused and resumed
+//
+// the clock is a parameter, so the demo below drives a ManualClock by hand and never actually sleeps
+
+pub trait Clock {
+    // time since some fixed point, only differences between two readings matter
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+// time only moves when advance() is called, and sleeping just moves it forward
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Running,
+    Paused,
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    // 1 for the first tick
+    pub number: u32,
+    // the number a countdown shows on this tick, None for an interval timer
+    pub remaining: Option<u32>,
+    // running time when the tick was due, which leaves out the time spent paused
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Tick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.remaining {
+            Some(remaining) => write!(f, "{}!", remaining),
+            None => write!(f, "tick {}", self.number),
+        }
+    }
+}
+
+type TickCallback<'a> = Box<dyn FnMut(&Tick) + 'a>;
+
+pub struct Timer<'a, C: Clock> {
+    clock: C,
+    interval: Duration,
+    // Some(n) for a countdown from n
+    countdown: Option<u32>,
+    state: State,
+    ticks: u32,
+    // running time from before the last pause, plus the clock reading when it was last started or resumed
+    banked: Duration,
+    resumed_at: Option<Duration>,
+    on_tick: Option<TickCallback<'a>>,
+    on_complete: Option<Box<dyn FnMut() + 'a>>,
+}
+
+impl<'a, C: Clock> Timer<'a, C> {
+    // ticks `from`, `from - 1`, ... `1`, one interval apart and starting right away like the loops do,
+    // and completes one interval after the last number
+    pub fn countdown(clock: C, from: u32, interval: Duration) -> Timer<'a, C> {
+        Timer::new(clock, Some(from), interval)
+    }
+
+    // ticks once every interval, starting one interval from now, until stop() is called
+    pub fn interval(clock: C, interval: Duration) -> Timer<'a, C> {
+        Timer::new(clock, None, interval)
+    }
+
+    fn new(clock: C, countdown: Option<u32>, interval: Duration) -> Timer<'a, C> {
+        assert!(!interval.is_zero(), "a timer interval must not be zero");
+        let resumed_at = Some(clock.now());
+        Timer {
+            clock,
+            interval,
+            countdown,
+            state: State::Running,
+            ticks: 0,
+            banked: Duration::ZERO,
+            resumed_at,
+            on_tick: None,
+            on_complete: None,
+        }
+    }
+
+    pub fn on_tick(mut self, callback: impl FnMut(&Tick) + 'a) -> Timer<'a, C> {
+        self.on_tick = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_complete(mut self, callback: impl FnMut() + 'a) -> Timer<'a, C> {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    // the number a countdown would show now, None for an interval timer
+    pub fn remaining(&self) -> Option<u32> {
+        self.countdown.map(|from| from - self.ticks.min(from))
+    }
+
+    // how long the timer has been running, not counting pauses
+    pub fn elapsed(&self) -> Duration {
+        match self.resumed_at {
+            Some(resumed_at) => self.banked + self.clock.now().saturating_sub(resumed_at),
+            None => self.banked,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == State::Running {
+            self.banked = self.elapsed();
+            self.resumed_at = None;
+            self.state = State::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == State::Paused {
+            self.resumed_at = Some(self.clock.now());
+            self.state = State::Running;
+        }
+    }
+
+    // stops an interval timer (or a countdown early), without calling on_complete
+    pub fn stop(&mut self) {
+        self.pause();
+        self.state = State::Finished;
+    }
+
+    // when the next tick, or the completion of a countdown, is due in running time
+    // None when that is past what a Duration or the tick count can hold, so it never comes
+    fn next_due(&self) -> Option<Duration> {
+        let index = match self.countdown {
+            Some(_) => self.ticks,
+            None => self.ticks.checked_add(1)?,
+        };
+        self.interval.checked_mul(index)
+    }
+
+    // how long until something happens, None once the timer has finished or has nothing left to do
+    pub fn time_until_next(&self) -> Option<Duration> {
+        match self.state {
+            State::Finished => None,
+            _ => Some(self.next_due()?.saturating_sub(self.elapsed())),
+        }
+    }
+
+    // fires everything that has come due since the last poll
+    // a late poll fires every missed tick in order, so a countdown never skips a number
+    pub fn poll(&mut self) -> State {
+        while self.state == State::Running {
+            // a timer whose next tick can never come stops, like stop() does
+            let Some(elapsed) = self.next_due() else {
+                self.stop();
+                break;
+            };
+            if elapsed > self.elapsed() {
+                break;
+            }
+            match self.countdown {
+                Some(from) if self.ticks == from => {
+                    self.pause();
+                    self.state = State::Finished;
+                    if let Some(on_complete) = self.on_complete.as_mut() {
+                        on_complete();
+                    }
+                }
+                countdown => {
+                    self.ticks += 1;
+                    let tick = Tick {
+                        number: self.ticks,
+                        remaining: countdown.map(|from| from - self.ticks + 1),
+                        elapsed,
+                    };
+                    if let Some(on_tick) = self.on_tick.as_mut() {
+                        on_tick(&tick);
+                    }
+                }
+            }
+        }
+        self.state
+    }
+
+    // sleeps on the clock between ticks until the countdown completes
+    // returns early if the timer is paused, and would never return for a running interval timer
+    pub fn run(&mut self) -> State {
+        while self.poll() == State::Running {
+            if let Some(wait) = self.time_until_next() {
+                self.clock.sleep(wait);
+            }
+        }
+        self.state
+    }
+}
+
+pub fn timer() {
+    let second = Duration::from_secs(1);
+    let clock = ManualClock::new();
+    let ticks = RefCell::new(Vec::new());
+    let completed = Cell::new(false);
+
+    let mut countdown = Timer::countdown(&clock, 3, second)
+        .on_tick(|tick| ticks.borrow_mut().push(tick.to_string()))
+        .on_complete(|| completed.set(true));
+    assert_eq!(countdown.poll(), State::Running);
+    assert_eq!(*ticks.borrow(), ["3!"]);
+    assert_eq!(countdown.time_until_next(), Some(second));
+
+    clock.advance(Duration::from_millis(1500));
+    countdown.poll();
+    assert_eq!(*ticks.borrow(), ["3!", "2!"]);
+    assert_eq!(countdown.remaining(), Some(1));
+
+    // time spent paused doesn't count
+    countdown.pause();
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(countdown.poll(), State::Paused);
+    assert_eq!(countdown.elapsed(), Duration::from_millis(1500));
+    assert_eq!(countdown.run(), State::Paused);
+    countdown.resume();
+    assert_eq!(
+        countdown.time_until_next(),
+        Some(Duration::from_millis(500))
+    );
+
+    // run() sleeps on the manual clock, so it finishes instantly
+    let started = clock.now();
+    assert_eq!(countdown.run(), State::Finished);
+    assert_eq!(*ticks.borrow(), ["3!", "2!", "1!"]);
+    assert!(completed.get());
+    assert_eq!(clock.now() - started, Duration::from_millis(1500));
+    assert_eq!(countdown.time_until_next(), None);
+    drop(countdown);
+
+    // a late poll catches up on every tick that was missed, each with the time it was due
+    let due = RefCell::new(Vec::new());
+    let mut interval = Timer::interval(&clock, Duration::from_millis(250)).on_tick(|tick| {
+        due.borrow_mut()
+            .push((tick.number, tick.elapsed.as_millis()))
+    });
+    clock.advance(Duration::from_millis(800));
+    interval.poll();
+    assert_eq!(*due.borrow(), [(1, 250), (2, 500), (3, 750)]);
+    interval.stop();
+    clock.advance(second);
+    assert_eq!(interval.poll(), State::Finished);
+    assert_eq!(interval.ticks(), 3);
+    drop(interval);
+
+    // a countdown from 0 has nothing to show, so it is over as soon as it is polled
+    let mut empty = Timer::countdown(&clock, 0, second);
+    assert_eq!(empty.state(), State::Running);
+    assert_eq!(empty.poll(), State::Finished);
+    assert_eq!(empty.ticks(), 0);
+
+    // the second tick of this one would be due past Duration::MAX, so it stops after the first
+    let clock = ManualClock::new();
+    let long = Duration::MAX / 2 + second;
+    let mut once = Timer::interval(&clock, long);
+    assert_eq!(once.time_until_next(), Some(long));
+    clock.advance(long);
+    assert_eq!(once.poll(), State::Finished);
+    assert_eq!(once.ticks(), 1);
+    assert_eq!(once.time_until_next(), None);
+
+    // the countdown from main.rs, on the real clock this time
+    let mut liftoff = Timer::countdown(SystemClock::new(), 3, Duration::from_millis(100))
+        .on_tick(|tick| println!("{}", tick))
+        .on_complete(|| println!("LIFTOFF!!!"));
+    liftoff.run();
+    assert!(liftoff.elapsed() >= Duration::from_millis(300));
+}
@@ -0,0 +1,68 @@
+// SmallString against String, with criterion: `cargo bench --bench small_string`
+// criterion builds with optimizations, warms each case up, then times it over many runs and reports
+// the spread, so the numbers mean something (unlike timing one debug run with Instant)
+//
+// each iteration only makes and drops the strings themselves; nothing else is allocated around them
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ownership::small_string::SmallString;
+
+// short enough to stay inline in a SmallString
+const SHORT: [&str; 5] = ["id", "name", "user_42", "hello, world", "ok"];
+// too long for that, so both types go to the heap
+const LONG: &str = "a label that is too long to fit inline";
+
+fn from_str(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from short");
+    group.bench_function("String", |b| {
+        b.iter(|| SHORT.map(|word| String::from(black_box(word))))
+    });
+    group.bench_function("SmallString", |b| {
+        b.iter(|| SHORT.map(|word| SmallString::from(black_box(word))))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("from long");
+    group.bench_function("String", |b| b.iter(|| String::from(black_box(LONG))));
+    group.bench_function("SmallString", |b| {
+        b.iter(|| SmallString::from(black_box(LONG)))
+    });
+    group.finish();
+}
+
+fn clone(c: &mut Criterion) {
+    let strings = SHORT.map(String::from);
+    let small = SHORT.map(SmallString::from);
+    let mut group = c.benchmark_group("clone short");
+    group.bench_function("String", |b| b.iter(|| black_box(&strings).clone()));
+    group.bench_function("SmallString", |b| b.iter(|| black_box(&small).clone()));
+    group.finish();
+}
+
+// grows from empty past the inline capacity, so SmallString moves to the heap part way through
+fn push_str(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_str");
+    group.bench_function("String", |b| {
+        b.iter(|| {
+            let mut s = String::new();
+            for word in SHORT {
+                s.push_str(black_box(word));
+            }
+            s
+        })
+    });
+    group.bench_function("SmallString", |b| {
+        b.iter(|| {
+            let mut s = SmallString::new();
+            for word in SHORT {
+                s.push_str(black_box(word));
+            }
+            s
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, from_str, clone, push_str);
+criterion_main!(benches);
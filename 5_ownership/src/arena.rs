@@ -0,0 +1,270 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::str;
+
+// calculate_length(&s1) in references.rs borrows one String from one owner
+// an Arena is an owner for lots of values at once: alloc moves a value into the arena and hands back a
+// reference to it, which is good for as long as the arena is, and everything is freed together when it drops
+//
+//     let arena = Arena::new();
+//     let x: &mut i32 = arena.alloc(5);
+//
+// alloc only needs &self, so any number of references can be handed out and kept at the same time,
+// and values can even point at each other, which Box and plain references can't do
+// reset needs &mut self, so the compiler makes sure none of them are left when it frees everything
+
+const FIRST_CHUNK: usize = 8;
+
+struct Chunks<T> {
+    // values are only ever added while there is room, so this Vec never reallocates and nothing in it moves
+    // they are written through raw pointers into the spare capacity: a &mut [T] over the chunk, from
+    // last_mut or push, would invalidate the &mut T already handed out for the values before it
+    current: Vec<T>,
+    // chunks that were full, kept so their values live on
+    full: Vec<Vec<T>>,
+}
+
+impl<T> Chunks<T> {
+    fn room(&self) -> usize {
+        self.current.capacity() - self.current.len()
+    }
+
+    // starts a new chunk with room for at least `needed` more values; the old one is kept as it is
+    fn grow(&mut self, needed: usize) {
+        let capacity = (self.current.capacity() * 2).max(needed).max(FIRST_CHUNK);
+        let full = mem::replace(&mut self.current, Vec::with_capacity(capacity));
+        if !full.is_empty() {
+            self.full.push(full);
+        }
+    }
+}
+
+/// the borrow checker keeps every reference from alloc inside the arena's lifetime
+///
+/// ```
+/// # use ownership::arena::Arena;
+/// let long_lived = Arena::new();
+/// let r;
+/// {
+///     r = long_lived.alloc(5);
+/// }
+/// assert_eq!(*r, 5);
+/// ```
+///
+/// a reference can't outlive its arena
+///
+/// ```compile_fail,E0597
+/// # use ownership::arena::Arena;
+/// let r;
+/// {
+///     let short_lived = Arena::new();
+///     r = short_lived.alloc(5); // error: `short_lived` does not live long enough
+/// }
+/// assert_eq!(*r, 5);
+/// ```
+///
+/// or be returned from the function that owns the arena, which is what would catch alloc ever handing
+/// out a &'static by mistake
+///
+/// ```
+/// # use ownership::arena::Arena;
+/// fn no_dangle(arena: &Arena<String>) -> &mut String {
+///     arena.alloc(String::from("hello"))
+/// }
+/// assert_eq!(no_dangle(&Arena::new()), "hello");
+/// ```
+///
+/// ```compile_fail,E0515
+/// # use ownership::arena::Arena;
+/// fn dangle() -> &'static mut String {
+///     let arena = Arena::new();
+///     arena.alloc(String::from("hello")) // error: cannot return value referencing local variable `arena`
+/// }
+/// ```
+///
+/// and reset can't free values that are still borrowed
+///
+/// ```
+/// # use ownership::arena::Arena;
+/// let mut numbers = Arena::new();
+/// let n = *numbers.alloc(1);
+/// numbers.reset();
+/// assert_eq!(n, 1);
+/// ```
+///
+/// ```compile_fail,E0502
+/// # use ownership::arena::Arena;
+/// let mut numbers = Arena::new();
+/// let n = numbers.alloc(1);
+/// numbers.reset(); // error: cannot borrow `numbers` as mutable because it is also borrowed as immutable
+/// assert_eq!(*n, 1);
+/// ```
+pub struct Arena<T> {
+    chunks: RefCell<Chunks<T>>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            chunks: RefCell::new(Chunks {
+                current: Vec::new(),
+                full: Vec::new(),
+            }),
+        }
+    }
+
+    // (clippy warns about &mut from &self, which is only sound because every call returns a new value)
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.room() == 0 {
+            chunks.grow(1);
+        }
+        let len = chunks.current.len();
+        // SAFETY: there is room for one more, so the slot at len is inside the chunk and unused;
+        // the value stays where it is until the arena is dropped or reset, and both of those need the
+        // arena to no longer be borrowed; each call hands out a different value, so the &mut is unique
+        unsafe {
+            let slot = chunks.current.as_mut_ptr().add(len);
+            ptr::write(slot, value);
+            chunks.current.set_len(len + 1);
+            &mut *slot
+        }
+    }
+
+    // how many values have been allocated since the arena was made or last reset
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.full.iter().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // drops every value, but keeps the biggest chunk to fill again
+    pub fn reset(&mut self) {
+        let chunks = self.chunks.get_mut();
+        chunks.full.clear();
+        chunks.current.clear();
+    }
+}
+
+impl<T: Copy> Arena<T> {
+    // copies the whole slice into one chunk, so it comes back as one slice
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice_copy(&self, values: &[T]) -> &mut [T] {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks.room() < values.len() {
+            chunks.grow(values.len());
+        }
+        let start = chunks.current.len();
+        // SAFETY: as in alloc, with room for all of values after start, which can't overlap them
+        unsafe {
+            let slots = chunks.current.as_mut_ptr().add(start);
+            ptr::copy_nonoverlapping(values.as_ptr(), slots, values.len());
+            chunks.current.set_len(start + values.len());
+            slice::from_raw_parts_mut(slots, values.len())
+        }
+    }
+}
+
+impl Arena<u8> {
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, string: &str) -> &mut str {
+        let bytes = self.alloc_slice_copy(string.as_bytes());
+        // SAFETY: the bytes were copied from a str, so they are valid UTF-8
+        unsafe { str::from_utf8_unchecked_mut(bytes) }
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Arena<T> {
+        Arena::new()
+    }
+}
+
+// a node that can point to other nodes in the same arena, cycles included
+struct Node<'a> {
+    name: &'a str,
+    next: Cell<Option<&'a Node<'a>>>,
+}
+
+// something to count the drops of
+struct Noisy<'a>(&'a Cell<usize>);
+
+impl Drop for Noisy<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+pub fn arena() {
+    let arena = Arena::new();
+    let x = arena.alloc(5);
+    let y = arena.alloc(10);
+    // two &mut at once, to two different values in the same arena
+    *x += 1;
+    *y += 1;
+    assert_eq!((*x, *y), (6, 11));
+
+    // lots of values, and every reference stays valid while new chunks are added
+    let all: Vec<&mut i32> = (0..100).map(|i| arena.alloc(i)).collect();
+    assert_eq!(all[0], &0);
+    assert_eq!(*all[99], 99);
+    assert_eq!(arena.len(), 102);
+    assert_eq!(*x, 6);
+
+    let slice = arena.alloc_slice_copy(&[1, 2, 3]);
+    slice[0] = 100;
+    assert_eq!(slice, &[100, 2, 3]);
+
+    // strings go into an arena of bytes
+    let text = Arena::new();
+    let hello = text.alloc_str("hello");
+    let world = text.alloc_str("wörld");
+    hello.make_ascii_uppercase();
+    assert_eq!(format!("{}, {}", hello, world), "HELLO, wörld");
+    assert_eq!(text.len(), 11);
+
+    // nodes that point at each other in a cycle, which Box (one owner each) can't do
+    let nodes = Arena::new();
+    let names = Arena::new();
+    let a = nodes.alloc(Node {
+        name: names.alloc_str("a"),
+        next: Cell::new(None),
+    });
+    let b = nodes.alloc(Node {
+        name: names.alloc_str("b"),
+        next: Cell::new(Some(a)),
+    });
+    a.next.set(Some(b));
+    let mut walk = Vec::new();
+    let mut node: &Node = a;
+    for _ in 0..4 {
+        walk.push(node.name);
+        node = node.next.get().unwrap();
+    }
+    assert_eq!(walk, ["a", "b", "a", "b"]);
+
+    // reset and drop both drop every value at once
+    let drops = Cell::new(0);
+    let mut noisy = Arena::new();
+    for _ in 0..20 {
+        noisy.alloc(Noisy(&drops));
+    }
+    assert_eq!(drops.get(), 0);
+    noisy.reset();
+    assert_eq!(drops.get(), 20);
+    assert!(noisy.is_empty());
+    noisy.alloc(Noisy(&drops));
+    drop(noisy);
+    assert_eq!(drops.get(), 21);
+
+    // what the borrow checker stops, like dangle() in references.rs, is in the compile_fail examples on
+    // Arena, which `cargo test` checks
+
+    println!("{} {}", hello, world);
+}
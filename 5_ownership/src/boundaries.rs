@@ -0,0 +1,278 @@
+use std::fmt;
+use std::ops::{Bound, Range, RangeBounds};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// the ranges in slices.rs, like &s[0..5], count bytes, and indexing panics when a range ends in the middle
+// of a character: "é" is 2 bytes, "🦀" is 4, and "👍🏽" is 8 bytes but only one thing on the screen
+// everything here hands out the same kind of &str slice, but never panics:
+//
+//     slice_bytes(s, 0..2)      byte offsets like &s[0..2], an Err instead of a panic
+//     slice_chars(s, 0..2)      the first two chars, None if s is shorter
+//     slice_graphemes(s, 0..2)  the first two user-perceived characters ("👍🏽" is one, "e\u{301}" is one)
+//     clamp_bytes(s, 0..2)      byte offsets moved inwards to the nearest char boundary, always succeeds
+//     truncate_to_width(s, 10)  as many whole graphemes as fit in 10 terminal columns
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceError {
+    // the range goes past the end of the string
+    OutOfBounds {
+        index: usize,
+        len: usize,
+    },
+    // start comes after end
+    Reversed {
+        start: usize,
+        end: usize,
+    },
+    // the index is inside the UTF-8 encoding of a char, which takes up the given bytes
+    NotCharBoundary {
+        index: usize,
+        inside: char,
+        bytes: Range<usize>,
+    },
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SliceError::OutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "byte index {} is out of bounds of a {} byte string",
+                    index, len
+                )
+            }
+            SliceError::Reversed { start, end } => {
+                write!(f, "slice starts at byte {} but ends at byte {}", start, end)
+            }
+            SliceError::NotCharBoundary {
+                index,
+                inside,
+                bytes,
+            } => write!(
+                f,
+                "byte index {} is not a char boundary; it is inside {:?} (bytes {:?})",
+                index, inside, bytes
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+// turns any range (0..2, ..2, 3.., ..=4, ..) into start and end, with the end not included
+// and None for a range with no end, which is different from one that ends at usize::MAX
+fn bounds(range: impl RangeBounds<usize>) -> (usize, Option<usize>) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => Some(end.saturating_add(1)),
+        Bound::Excluded(&end) => Some(end),
+        Bound::Unbounded => None,
+    };
+    (start, end)
+}
+
+fn check_boundary(s: &str, index: usize) -> Result<(), SliceError> {
+    if index > s.len() {
+        return Err(SliceError::OutOfBounds {
+            index,
+            len: s.len(),
+        });
+    }
+    if s.is_char_boundary(index) {
+        return Ok(());
+    }
+    let start = floor_char_boundary(s, index);
+    let inside = s[start..].chars().next().unwrap();
+    Err(SliceError::NotCharBoundary {
+        index,
+        inside,
+        bytes: start..start + inside.len_utf8(),
+    })
+}
+
+// the same as &s[range], with the panic turned into an error
+pub fn slice_bytes(s: &str, range: impl RangeBounds<usize>) -> Result<&str, SliceError> {
+    let (start, end) = bounds(range);
+    let end = end.unwrap_or(s.len());
+    check_boundary(s, start)?;
+    check_boundary(s, end)?;
+    if start > end {
+        return Err(SliceError::Reversed { start, end });
+    }
+    Ok(&s[start..end])
+}
+
+// the closest char boundary at or before index, or the end of s
+pub fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    // a char is at most 4 bytes long, so a boundary is never more than 3 bytes back
+    (index.saturating_sub(3)..=index)
+        .rev()
+        .find(|&i| s.is_char_boundary(i))
+        .unwrap()
+}
+
+// the closest char boundary at or after index, or the end of s
+pub fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    (index..=s.len()).find(|&i| s.is_char_boundary(i)).unwrap()
+}
+
+// byte offsets that land inside a char are moved inwards, so the slice never holds part of a char
+// and never grows past what was asked for
+pub fn clamp_bytes(s: &str, range: impl RangeBounds<usize>) -> &str {
+    let (start, end) = bounds(range);
+    let end = end.unwrap_or(s.len());
+    let start = ceil_char_boundary(s, start);
+    let end = floor_char_boundary(s, end);
+    if start >= end {
+        return "";
+    }
+    &s[start..end]
+}
+
+// byte offsets of the indexes in a range of pieces (chars or graphemes), given where each piece starts
+fn byte_range(
+    s: &str,
+    starts: impl Iterator<Item = usize>,
+    range: impl RangeBounds<usize>,
+) -> Option<Range<usize>> {
+    let (start, end) = bounds(range);
+    if end.is_some_and(|end| start > end) {
+        return None;
+    }
+    let mut start_byte = None;
+    // the end of the string counts as the start of one more piece, so a range can end there
+    for (n, byte) in starts.chain(Some(s.len())).enumerate() {
+        if n == start {
+            start_byte = Some(byte);
+        }
+        if Some(n) == end {
+            return Some(start_byte?..byte);
+        }
+    }
+    // the range is unbounded at the end, or ends past the last piece
+    match (start_byte, end) {
+        (Some(start_byte), None) => Some(start_byte..s.len()),
+        _ => None,
+    }
+}
+
+// counts chars rather than bytes, so slice_chars("héllo", 0..2) is "hé"
+pub fn slice_chars(s: &str, range: impl RangeBounds<usize>) -> Option<&str> {
+    let bytes = byte_range(s, s.char_indices().map(|(i, _)| i), range)?;
+    Some(&s[bytes])
+}
+
+// counts what a reader would call one character, even when it is made of several chars
+pub fn slice_graphemes(s: &str, range: impl RangeBounds<usize>) -> Option<&str> {
+    let bytes = byte_range(s, s.grapheme_indices(true).map(|(i, _)| i), range)?;
+    Some(&s[bytes])
+}
+
+// the longest start of s that fits in width columns, without cutting a grapheme in half
+// most chars take one column, East Asian ones and most emoji take two, and combining marks none
+pub fn truncate_to_width(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        used += grapheme.width();
+        if used > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+pub fn boundaries() {
+    let s = "héllo wörld";
+    // &s[0..2] would panic, because byte 2 is in the middle of 'é'
+    assert_eq!(slice_bytes(s, 0..1), Ok("h"));
+    assert_eq!(slice_bytes(s, 0..3), Ok("hé"));
+    let error = slice_bytes(s, 0..2).unwrap_err();
+    assert_eq!(
+        error,
+        SliceError::NotCharBoundary {
+            index: 2,
+            inside: 'é',
+            bytes: 1..3
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "byte index 2 is not a char boundary; it is inside 'é' (bytes 1..3)"
+    );
+    assert_eq!(
+        slice_bytes(s, 7..20),
+        Err(SliceError::OutOfBounds { index: 20, len: 13 })
+    );
+    let (start, end) = (3, 1);
+    assert_eq!(
+        slice_bytes(s, start..end),
+        Err(SliceError::Reversed { start: 3, end: 1 })
+    );
+    assert_eq!(slice_bytes(s, ..), Ok(s));
+
+    // clamping moves each end inwards to the nearest boundary
+    assert_eq!(clamp_bytes(s, 0..2), "h");
+    assert_eq!(clamp_bytes(s, 2..), "llo wörld");
+    assert_eq!(clamp_bytes(s, 2..2), "");
+    assert_eq!(clamp_bytes(s, 6..100), " wörld");
+    assert_eq!(floor_char_boundary("🦀", 3), 0);
+    assert_eq!(ceil_char_boundary("🦀", 1), 4);
+
+    // by char: the same indexes as the byte ranges in slices.rs, for text that isn't ASCII
+    assert_eq!(slice_chars(s, 0..5), Some("héllo"));
+    assert_eq!(slice_chars(s, 6..11), Some("wörld"));
+    assert_eq!(slice_chars(s, 6..), Some("wörld"));
+    assert_eq!(slice_chars(s, ..=1), Some("hé"));
+    assert_eq!(slice_chars(s, 11..), Some(""));
+    assert_eq!(slice_chars(s, 6..12), None);
+    assert_eq!(slice_chars(s, 12..), None);
+    // an end this far out is still out of range, not the same as leaving the end off
+    assert_eq!(slice_chars(s, 0..usize::MAX), None);
+    assert_eq!(slice_chars(s, ..=usize::MAX), None);
+    assert_eq!(slice_graphemes(s, 6..usize::MAX), None);
+    assert_eq!(slice_chars("", ..), Some(""));
+
+    // by grapheme: a thumbs up with a skin tone is two chars, and e with a combining accent is two as well
+    let emoji = "👍🏽 ok e\u{301}";
+    assert_eq!(emoji.chars().count(), 8);
+    assert_eq!(emoji.graphemes(true).count(), 6);
+    assert_eq!(slice_chars(emoji, 0..1), Some("👍"));
+    assert_eq!(slice_graphemes(emoji, 0..1), Some("👍🏽"));
+    assert_eq!(slice_graphemes(emoji, 2..4), Some("ok"));
+    assert_eq!(slice_graphemes(emoji, 5..), Some("e\u{301}"));
+    assert_eq!(slice_graphemes(emoji, 5..7), None);
+    let family = "👨‍👩‍👧 family";
+    assert_eq!(slice_graphemes(family, ..1), Some("👨‍👩‍👧"));
+
+    // by width: what fits in a column of a terminal or a table
+    assert_eq!(truncate_to_width("hello world", 5), "hello");
+    assert_eq!(truncate_to_width("hello", 10), "hello");
+    assert_eq!(truncate_to_width("東京タワー", 5), "東京");
+    assert_eq!(truncate_to_width("🦀🦀🦀", 3), "🦀");
+    assert_eq!(truncate_to_width("👍🏽 ok", 1), "");
+    assert_eq!(truncate_to_width("👍🏽 ok", 2), "👍🏽");
+    assert_eq!(truncate_to_width("cafe\u{301}!", 4), "cafe\u{301}");
+
+    // every one of them gives back part of s itself, like the slices in slices.rs
+    let part = slice_chars(s, 6..).unwrap();
+    assert_eq!(part.as_ptr(), s[7..].as_ptr());
+
+    println!(
+        "{} | {}",
+        slice_graphemes(emoji, ..1).unwrap(),
+        truncate_to_width("東京タワー", 5)
+    );
+}
@@ -0,0 +1,199 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ownership::small_string::SmallString;
+
+// main.rs says String::from "requests the memory it needs" from the allocator, and a literal doesn't
+// CountingAlloc sits in front of the system allocator and counts every request, so that can be checked
+//
+// it is opt-in: only `cargo run --features count-allocations` installs it as the #[global_allocator]
+// (see main.rs), and without it every count below stays at 0
+//
+// the counters are shared by every thread, so a measurement also counts what other threads allocate meanwhile
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static DEALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static BYTES_IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK_IN_USE: AtomicUsize = AtomicUsize::new(0);
+
+// (unused unless the feature is on)
+#[cfg_attr(not(feature = "count-allocations"), allow(dead_code))]
+pub struct CountingAlloc;
+
+#[cfg_attr(not(feature = "count-allocations"), allow(dead_code))]
+fn grew(bytes: usize) {
+    BYTES_ALLOCATED.fetch_add(bytes, Ordering::Relaxed);
+    let in_use = BYTES_IN_USE.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    PEAK_IN_USE.fetch_max(in_use, Ordering::Relaxed);
+}
+
+#[cfg_attr(not(feature = "count-allocations"), allow(dead_code))]
+fn shrank(bytes: usize) {
+    BYTES_IN_USE.fetch_sub(bytes, Ordering::Relaxed);
+}
+
+// SAFETY: every call goes straight to System; the counting around it never allocates
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        shrank(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            REALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            if new_size > layout.size() {
+                grew(new_size - layout.size());
+            } else {
+                shrank(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub allocations: usize,
+    pub reallocations: usize,
+    pub deallocations: usize,
+    // everything asked for, including what has been freed again
+    pub bytes: usize,
+    // the most that was in use at once
+    pub peak: usize,
+}
+
+// the totals since the program started
+pub fn stats() -> Stats {
+    Stats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        reallocations: REALLOCATIONS.load(Ordering::Relaxed),
+        deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+        bytes: BYTES_ALLOCATED.load(Ordering::Relaxed),
+        peak: PEAK_IN_USE.load(Ordering::Relaxed),
+    }
+}
+
+// counts from when it is started; the peak is measured above what was already in use at that point
+// (starting a scope resets the peak, so scopes shouldn't be nested)
+pub struct Scope {
+    start: Stats,
+    in_use: usize,
+}
+
+impl Scope {
+    pub fn start() -> Scope {
+        let in_use = BYTES_IN_USE.load(Ordering::Relaxed);
+        PEAK_IN_USE.store(in_use, Ordering::Relaxed);
+        Scope {
+            start: stats(),
+            in_use,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let now = stats();
+        Stats {
+            allocations: now.allocations - self.start.allocations,
+            reallocations: now.reallocations - self.start.reallocations,
+            deallocations: now.deallocations - self.start.deallocations,
+            bytes: now.bytes - self.start.bytes,
+            peak: now.peak.saturating_sub(self.in_use),
+        }
+    }
+}
+
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, Stats) {
+    let scope = Scope::start();
+    let result = f();
+    (result, scope.stats())
+}
+
+// true when CountingAlloc is the global allocator, which only the count-allocations feature does
+pub fn is_enabled() -> bool {
+    let (_, stats) = measure(|| black_box(Box::new(0u8)));
+    stats.allocations == 1
+}
+
+pub fn counting_alloc() {
+    if !is_enabled() {
+        println!("run with `--features count-allocations` to count allocations");
+        return;
+    }
+
+    // a string literal is already in the binary, so there is nothing to ask for
+    let (_, literal) = measure(|| black_box("hello"));
+    assert_eq!(literal, Stats::default());
+
+    // String::from asks for exactly the 5 bytes of "hello", and gives them back when the String is dropped
+    let (s1, from) = measure(|| String::from("hello"));
+    assert_eq!((from.allocations, from.bytes, from.peak), (1, 5, 5));
+    assert_eq!(from.deallocations, 0);
+
+    // clone copies the heap bytes, so it asks for another 5
+    let (s2, clone) = measure(|| s1.clone());
+    assert_eq!((clone.allocations, clone.bytes), (1, 5));
+
+    // a move only copies ptr, len and capacity
+    let (s3, moved) = measure(|| black_box(s2));
+    assert_eq!(moved, Stats::default());
+
+    let ((), dropped) = measure(|| drop(s3));
+    assert_eq!((dropped.allocations, dropped.deallocations), (0, 1));
+
+    // push_str past the capacity reallocates: 5 bytes become 13, and nothing else is asked for
+    let (s, grown) = measure(|| {
+        let mut s = String::from("hello");
+        s.push_str(", world!");
+        s
+    });
+    assert_eq!(s.capacity(), 13);
+    assert_eq!((grown.allocations, grown.reallocations), (1, 1));
+    assert_eq!((grown.bytes, grown.peak), (13, 13));
+
+    // with_capacity asks once, up front, however many pushes follow
+    let (_, reserved) = measure(|| {
+        let mut s = String::with_capacity(64);
+        for _ in 0..8 {
+            s.push_str("hello, ");
+        }
+        s
+    });
+    assert_eq!((reserved.allocations, reserved.reallocations), (1, 0));
+    assert_eq!(reserved.bytes, 64);
+
+    // and the types from small_string.rs: a short SmallString never allocates
+    let (small, inline) = measure(|| SmallString::from("hello"));
+    assert_eq!(inline, Stats::default());
+    let (_, cloned) = measure(|| small.clone() == "hello");
+    assert_eq!(cloned, Stats::default());
+    // (text_pipeline.rs checks its own zero-copy paths the same way)
+
+    println!(
+        "String::from: {:?}\nclone: {:?}\npush_str: {:?}",
+        from, clone, grown
+    );
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// a struct like User in 6_structs owns a String for every username, so a million users called "admin"
+// hold a million copies of "admin"
+// an Interner keeps one copy of each string and hands out a Symbol for it instead:
+//
+//     let admin = interner.intern("admin");  // a Symbol is a u32: Copy, and compared like one
+//     interner.resolve(admin)                 // "admin", borrowed from the interner
+//
+// the &str from resolve borrows the interner, so the usual rules keep it valid: the interner can't be
+// changed or dropped while the &str is still in use
+// SyncInterner is the same thing behind a lock, for sharing between threads
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    // symbols count up from 0 in the order their strings were first interned
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+pub struct Interner {
+    // the keys borrow from the boxes in strings, which is why this is declared (and so dropped) first
+    symbols: HashMap<&'static str, Symbol>,
+    // every string once, in symbol order; each is boxed, so its bytes stay put when the Vec grows
+    strings: Vec<Box<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner {
+            symbols: HashMap::new(),
+            strings: Vec::new(),
+        }
+    }
+
+    // the same string always gets the same symbol
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+        let symbol = Symbol(u32::try_from(self.strings.len()).expect("too many strings to intern"));
+        self.strings.push(string.into());
+        let stored: *const str = &**self.strings.last().unwrap();
+        // SAFETY: the bytes are on the heap and never move, change or get freed until the interner is dropped,
+        // and the map that holds this reference is dropped before them
+        let key: &'static str = unsafe { &*stored };
+        self.symbols.insert(key, symbol);
+        symbol
+    }
+
+    // the symbol of a string that has already been interned, without interning it
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.symbols.get(string).copied()
+    }
+
+    // panics if the symbol came from another interner with more strings in it
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+        self.strings
+            .iter()
+            .enumerate()
+            .map(|(i, string)| (Symbol(i as u32), &**string))
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Interner {
+        Interner::new()
+    }
+}
+
+// not derived: a derived clone would copy the map's references into the other interner's strings
+impl Clone for Interner {
+    fn clone(&self) -> Interner {
+        let mut interner = Interner::new();
+        for (_, string) in self.iter() {
+            interner.intern(string);
+        }
+        interner
+    }
+}
+
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.strings.iter()).finish()
+    }
+}
+
+// the table is saved as a list of the strings in symbol order, so every symbol means the same after loading
+impl Serialize for Interner {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.strings.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Interner {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Interner, D::Error> {
+        let strings = Vec::<String>::deserialize(deserializer)?;
+        let mut interner = Interner::new();
+        for (i, string) in strings.iter().enumerate() {
+            // a string in there twice would shift the symbols of everything after it
+            if interner.intern(string).index() != i {
+                return Err(D::Error::custom(format!(
+                    "{:?} is in the table more than once",
+                    string
+                )));
+            }
+        }
+        Ok(interner)
+    }
+}
+
+// can be shared with & between threads; interning takes the write lock only for strings it hasn't seen yet
+#[derive(Debug, Default)]
+pub struct SyncInterner {
+    inner: RwLock<Interner>,
+}
+
+impl SyncInterner {
+    pub fn new() -> SyncInterner {
+        SyncInterner::default()
+    }
+
+    pub fn intern(&self, string: &str) -> Symbol {
+        if let Some(symbol) = self.get(string) {
+            return symbol;
+        }
+        // another thread may have interned it between the two locks, which intern handles
+        self.inner.write().unwrap().intern(string)
+    }
+
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.inner.read().unwrap().get(string)
+    }
+
+    // the &str outlives the read lock, but not the SyncInterner
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        let interner = self.inner.read().unwrap();
+        let string: *const str = interner.resolve(symbol);
+        // SAFETY: strings are only ever added, each in a box of its own, so the bytes stay where they are
+        // until the Interner is dropped, and &self keeps the SyncInterner alive for as long as the &str
+        unsafe { &*string }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().len()
+    }
+
+    pub fn into_inner(self) -> Interner {
+        self.inner.into_inner().unwrap()
+    }
+}
+
+// 6_structs' User, with a symbol where the username String was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct User {
+    username: Symbol,
+    active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Saved {
+    usernames: Interner,
+}
+
+pub fn interner() {
+    let mut interner = Interner::new();
+    let names = ["admin", "guest", "admin", "ferris", "guest", "admin"];
+    let users: Vec<User> = names
+        .iter()
+        .map(|name| User {
+            username: interner.intern(name),
+            active: true,
+        })
+        .collect();
+
+    // six users, three strings
+    assert!(Interner::new().is_empty());
+    assert_eq!(interner.len(), 3);
+    assert_eq!(users[0].username, users[2].username);
+    assert_ne!(users[0].username, users[1].username);
+    assert!(users.iter().all(|user| user.active));
+    assert_eq!(interner.resolve(users[3].username), "ferris");
+    assert_eq!(interner.get("guest"), Some(users[1].username));
+    assert_eq!(interner.get("nobody"), None);
+    // a Symbol is 4 bytes and Copy, where a String is 24 bytes and has to be cloned
+    assert_eq!(std::mem::size_of::<Symbol>(), 4);
+
+    // every resolve of a string is the same copy of it
+    let first = interner.resolve(users[0].username);
+    let last = interner.resolve(users[5].username);
+    assert_eq!(first.as_ptr(), last.as_ptr());
+
+    // the borrowing rules at work: the resolved &str keeps the interner borrowed, so it can't be changed
+    // let name = interner.resolve(users[0].username);
+    // interner.intern("root"); // error: cannot borrow `interner` as mutable because it is also borrowed as immutable
+    // println!("{}", name);
+    let name = interner.resolve(users[0].username).to_string();
+    let root = interner.intern("root");
+    assert_eq!((name.as_str(), root.index()), ("admin", 3));
+
+    // saving the table keeps every symbol's meaning, so symbols can be saved alongside it
+    let saved = toml::to_string(&Saved {
+        usernames: interner.clone(),
+    })
+    .unwrap();
+    assert_eq!(
+        saved,
+        "usernames = [\"admin\", \"guest\", \"ferris\", \"root\"]\n"
+    );
+    let loaded: Saved = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.usernames.resolve(users[3].username), "ferris");
+    assert_eq!(loaded.usernames.get("root"), Some(root));
+    let broken = toml::from_str::<Saved>("usernames = [\"a\", \"b\", \"a\"]");
+    assert!(broken
+        .unwrap_err()
+        .to_string()
+        .contains("\"a\" is in the table more than once"));
+
+    // the same from four threads at once: they all agree on the symbols
+    let shared = SyncInterner::new();
+    let symbols: Vec<Vec<Symbol>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = &shared;
+                scope.spawn(move || names.iter().map(|name| shared.intern(name)).collect())
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert!(symbols.windows(2).all(|pair| pair[0] == pair[1]));
+    assert_eq!(shared.len(), 3);
+    let admin = shared.resolve(symbols[0][0]);
+    shared.intern("one more");
+    assert_eq!(admin, "admin");
+    assert_eq!(shared.into_inner().len(), 4);
+
+    println!("{:?}", interner);
+}
@@ -0,0 +1,4 @@
+// the modules that code outside src/main.rs needs as well: benches/small_string.rs compares
+// SmallString with String, and cargo test runs the compile_fail examples on Arena
+pub mod arena;
+pub mod small_string;
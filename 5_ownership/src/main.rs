@@ -1,5 +1,21 @@
-use self::references;
-use self::slices;
+mod boundaries;
+mod counting_alloc;
+mod interner;
+mod references;
+mod rope;
+mod slice_utils;
+mod slices;
+mod text_pipeline;
+mod tokenizer;
+mod tracked;
+
+use ownership::small_string;
+use tracked::{Tracer, Tracked};
+
+// counts every heap allocation, for counting_alloc.rs; only with `--features count-allocations`
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: counting_alloc::CountingAlloc = counting_alloc::CountingAlloc;
 
 fn main() {
     // ownership rules
@@ -102,29 +118,57 @@ fn main() {
 
     // ownership and functions
     // Passing a variable to a function will move or copy, just as assignment does.
-    let s = String::from("hello"); // s comes into scope
-
-    takes_ownership(s); // s's value moves into the function...
-                        // ... and so is no longer valid here
-
-    let x = 5; // x comes into scope
-
-    makes_copy(x); // x would move into the function,
-                   // but i32 is Copy, so it's okay to still
-                   // use x afterward
-                   // Here, x goes out of scope, then s. But because s's value was moved, nothing
-                   // special happens.
-
-    //
-    // returning values can also transfer ownership
-    let _s1 = gives_ownership(); // gives_ownership moves its return
-                                 // value into s1
-
-    let _s2 = String::from("hello"); // s2 comes into scope
-
-    let _s3 = takes_and_gives_back(_s2); // s2 is moved into
-                                         // takes_and_gives_back, which also
-                                         // moves its return value into s3
+    // The Strings here are wrapped in Tracked (see tracked.rs), which writes down each move and drop,
+    // so the comments below can be checked against what really happens
+    let tracer = Tracer::new();
+    {
+        let s = tracer.track("main", "s", String::from("hello")); // s comes into scope
+
+        takes_ownership(s); // s's value moves into the function...
+                            // ... and so is no longer valid here
+        assert_eq!(tracer.drops(), ["some_string"]); // ... where it was dropped at the closing bracket
+
+        let x = 5; // x comes into scope
+
+        makes_copy(x); // x would move into the function,
+                       // but i32 is Copy, so it's okay to still
+                       // use x afterward
+                       // Here, x goes out of scope, then s. But because s's value was moved, nothing
+                       // special happens.
+
+        //
+        // returning values can also transfer ownership
+        let _s1 = gives_ownership(&tracer).moved_to("main", "_s1"); // gives_ownership moves its return
+                                                                    // value into s1
+
+        let _s2 = tracer.track("main", "_s2", String::from("hello")); // s2 comes into scope
+
+        let _s3 = takes_and_gives_back(_s2).moved_to("main", "_s3"); // s2 is moved into
+                                                                     // takes_and_gives_back, which also
+                                                                     // moves its return value into s3
+        assert_eq!(tracer.drops(), ["some_string"]);
+    } // Here, s3 goes out of scope and is dropped. s2 was moved, so nothing
+      // happens. s1 goes out of scope and is dropped.
+    assert_eq!(tracer.drops(), ["some_string", "_s3", "_s1"]);
+    let trace: Vec<String> = tracer.events().iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        trace,
+        [
+            "#1 main: s created",
+            "#2 takes_ownership: some_string moved in from main (was s)",
+            "#3 takes_ownership: some_string dropped",
+            "#4 gives_ownership: some_string created",
+            "#5 main: _s1 moved in from gives_ownership (was some_string)",
+            "#6 main: _s2 created",
+            "#7 takes_and_gives_back: a_string moved in from main (was _s2)",
+            "#8 main: _s3 moved in from takes_and_gives_back (was a_string)",
+            "#9 main: _s3 dropped",
+            "#10 main: _s1 dropped",
+        ]
+    );
+    for lifetime in tracer.report() {
+        println!("{}", lifetime);
+    }
 
     // rust does let us return multiple values using a tuple
     let _s1 = String::from("hello");
@@ -133,14 +177,33 @@ fn main() {
 
     references::references();
     slices::slices();
+    // more things to do with array slices like &a[1..3], none of which copy anything
+    slice_utils::slice_utils();
+    // text too big to keep in one String, kept as slices of many smaller ones
+    rope::rope();
+    // first_word from slices.rs again, for text that isn't separated by plain spaces
+    tokenizer::tokenizer();
+    // the byte ranges from slices.rs, without the panics on text that isn't ASCII
+    boundaries::boundaries();
+    // ptr, len and capacity from above, with short strings kept inline instead of on the heap
+    small_string::small_string();
+    // takes_and_gives_back without giving anything back: borrow, and only copy when the text changes
+    text_pipeline::text_pipeline();
+    // more of what Tracked from the ownership and functions examples above can show
+    tracked::tracked();
+    // how many allocations String::from, clone and the rest actually make
+    counting_alloc::counting_alloc();
+    // one copy of each string, shared by borrowing instead of cloned
+    interner::interner();
 }
 
 fn println(str: &str) {
     println!("{}", str)
 }
 
-fn takes_ownership(some_string: String) {
+fn takes_ownership(some_string: Tracked<String>) {
     // some_string comes into scope
+    let some_string = some_string.moved_to("takes_ownership", "some_string");
     println!("{}", some_string);
 } // Here, some_string goes out of scope and `drop` is called. The backing
   // memory is freed.
@@ -150,12 +213,12 @@ fn makes_copy(some_integer: i32) {
     println!("{}", some_integer);
 } // Here, some_integer goes out of scope. Nothing special happens.
 
-fn gives_ownership() -> String {
+fn gives_ownership(tracer: &Tracer) -> Tracked<String> {
     // gives_ownership will move its
     // return value into the function
     // that calls it
 
-    let some_string = String::from("yours"); // some_string comes into scope
+    let some_string = tracer.track("gives_ownership", "some_string", String::from("yours")); // some_string comes into scope
 
     some_string // some_string is returned and
                 // moves out to the calling
@@ -163,9 +226,11 @@ fn gives_ownership() -> String {
 }
 
 // This function takes a String and returns one
-fn takes_and_gives_back(a_string: String) -> String {
+#[allow(clippy::let_and_return)]
+fn takes_and_gives_back(a_string: Tracked<String>) -> Tracked<String> {
     // a_string comes into
     // scope
+    let a_string = a_string.moved_to("takes_and_gives_back", "a_string");
 
     a_string // a_string is returned and moves out to the calling function
 }
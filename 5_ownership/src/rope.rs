@@ -0,0 +1,576 @@
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// a String keeps all of its text in one buffer, so inserting near the start of a big one moves everything after it
+// a Rope keeps the text in chunks of up to MAX_LEAF bytes at the leaves of a balanced tree,
+// and every branch remembers how many bytes, chars and newlines are under it:
+//
+//                 (chars 11, lines 1)
+//                  /               \
+//            "hello\n"            "world"
+//
+// finding a char index or a line only walks down one path, and an insert or delete only rebuilds that path,
+// so both take O(log n) however long the text is
+// the tree is kept balanced like an AVL tree: the two sides of a branch never differ in height by more than one
+
+const MAX_LEAF: usize = 512;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Metrics {
+    bytes: usize,
+    chars: usize,
+    newlines: usize,
+}
+
+impl Metrics {
+    fn of(text: &str) -> Metrics {
+        Metrics {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+
+    fn add(self, other: Metrics) -> Metrics {
+        Metrics {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        text: String,
+        metrics: Metrics,
+    },
+    Branch {
+        left: Box<Node>,
+        right: Box<Node>,
+        height: usize,
+        metrics: Metrics,
+    },
+}
+
+fn byte_of_char(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+impl Node {
+    // counts the text once, so no one has to count it again
+    fn leaf(text: String) -> Node {
+        Node::Leaf {
+            metrics: Metrics::of(&text),
+            text,
+        }
+    }
+
+    fn metrics(&self) -> Metrics {
+        match self {
+            Node::Leaf { metrics, .. } | Node::Branch { metrics, .. } => *metrics,
+        }
+    }
+
+    fn chars(&self) -> usize {
+        self.metrics().chars
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Node::Leaf { text, .. } if text.is_empty())
+    }
+
+    fn branch(left: Node, right: Node) -> Node {
+        Node::Branch {
+            height: left.height().max(right.height()) + 1,
+            metrics: left.metrics().add(right.metrics()),
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    // splits text into leaves of at most MAX_LEAF bytes, without cutting a char in half, and builds a balanced tree
+    fn build(text: &str) -> Node {
+        if text.len() <= MAX_LEAF {
+            return Node::leaf(text.to_string());
+        }
+        let mut middle = text.len() / 2;
+        while !text.is_char_boundary(middle) {
+            middle += 1;
+        }
+        let (left, right) = text.split_at(middle);
+        Node::branch(Node::build(left), Node::build(right))
+    }
+
+    fn into_parts(self) -> (Node, Node) {
+        match self {
+            Node::Branch { left, right, .. } => (*left, *right),
+            Node::Leaf { .. } => unreachable!("a leaf has no parts"),
+        }
+    }
+
+    // one rotation (or two) when the sides differ in height by two
+    fn rebalance(left: Node, right: Node) -> Node {
+        if left.height() > right.height() + 1 {
+            let (ll, lr) = left.into_parts();
+            if ll.height() >= lr.height() {
+                Node::branch(ll, Node::branch(lr, right))
+            } else {
+                let (lrl, lrr) = lr.into_parts();
+                Node::branch(Node::branch(ll, lrl), Node::branch(lrr, right))
+            }
+        } else if right.height() > left.height() + 1 {
+            let (rl, rr) = right.into_parts();
+            if rr.height() >= rl.height() {
+                Node::branch(Node::branch(left, rl), rr)
+            } else {
+                let (rll, rlr) = rl.into_parts();
+                Node::branch(Node::branch(left, rll), Node::branch(rlr, rr))
+            }
+        } else {
+            Node::branch(left, right)
+        }
+    }
+
+    // all of left followed by all of right, walking down the taller side until the heights are close
+    fn join(left: Node, right: Node) -> Node {
+        if left.is_empty() {
+            return right;
+        }
+        if right.is_empty() {
+            return left;
+        }
+        if let (Node::Leaf { text: l, .. }, Node::Leaf { text: r, .. }) = (&left, &right) {
+            if l.len() + r.len() <= MAX_LEAF {
+                return Node::leaf(l.clone() + r);
+            }
+        }
+        if left.height() > right.height() + 1 {
+            let (ll, lr) = left.into_parts();
+            Node::rebalance(ll, Node::join(lr, right))
+        } else if right.height() > left.height() + 1 {
+            let (rl, rr) = right.into_parts();
+            Node::rebalance(Node::join(left, rl), rr)
+        } else {
+            Node::branch(left, right)
+        }
+    }
+
+    // the first char_index chars, and the rest
+    fn split(self, char_index: usize) -> (Node, Node) {
+        match self {
+            Node::Leaf { mut text, .. } => {
+                let rest = text.split_off(byte_of_char(&text, char_index));
+                (Node::leaf(text), Node::leaf(rest))
+            }
+            Node::Branch { left, right, .. } => {
+                let left_chars = left.chars();
+                if char_index < left_chars {
+                    let (ll, lr) = left.split(char_index);
+                    (ll, Node::join(lr, *right))
+                } else if char_index == left_chars {
+                    (*left, *right)
+                } else {
+                    let (rl, rr) = right.split(char_index - left_chars);
+                    (Node::join(*left, rl), rr)
+                }
+            }
+        }
+    }
+
+    fn char_at(&self, char_index: usize) -> Option<char> {
+        match self {
+            Node::Leaf { text, .. } => text.chars().nth(char_index),
+            Node::Branch { left, right, .. } => {
+                let left_chars = left.chars();
+                if char_index < left_chars {
+                    left.char_at(char_index)
+                } else {
+                    right.char_at(char_index - left_chars)
+                }
+            }
+        }
+    }
+
+    // how many newlines there are in the first char_index chars
+    fn newlines_before(&self, char_index: usize) -> usize {
+        match self {
+            Node::Leaf { text, .. } => text.chars().take(char_index).filter(|&c| c == '\n').count(),
+            Node::Branch { left, right, .. } => {
+                let before = left.metrics();
+                if char_index <= before.chars {
+                    left.newlines_before(char_index)
+                } else {
+                    before.newlines + right.newlines_before(char_index - before.chars)
+                }
+            }
+        }
+    }
+
+    // the char index just after the n-th newline (counting from 0), which is where line n + 1 starts
+    fn after_newline(&self, n: usize) -> usize {
+        match self {
+            Node::Leaf { text, .. } => text
+                .chars()
+                .enumerate()
+                .filter(|&(_, c)| c == '\n')
+                .nth(n)
+                .map(|(i, _)| i + 1)
+                .unwrap(),
+            Node::Branch { left, right, .. } => {
+                let before = left.metrics();
+                if n < before.newlines {
+                    left.after_newline(n)
+                } else {
+                    before.chars + right.after_newline(n - before.newlines)
+                }
+            }
+        }
+    }
+
+    // true when every branch is balanced and knows the right metrics, for checking the tree in rope()
+    fn is_valid(&self) -> bool {
+        match self {
+            Node::Leaf { text, metrics } => text.len() <= MAX_LEAF && *metrics == Metrics::of(text),
+            Node::Branch {
+                left,
+                right,
+                height,
+                metrics,
+            } => {
+                left.height().abs_diff(right.height()) <= 1
+                    && *height == left.height().max(right.height()) + 1
+                    && *metrics == left.metrics().add(right.metrics())
+                    && !left.is_empty()
+                    && !right.is_empty()
+                    && left.is_valid()
+                    && right.is_valid()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rope {
+    root: Node,
+}
+
+impl Rope {
+    pub fn new() -> Rope {
+        Rope {
+            root: Node::leaf(String::new()),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.root.metrics().bytes
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.root.chars()
+    }
+
+    // like str::lines, except that a trailing newline starts one more (empty) line, the way an editor shows it
+    pub fn len_lines(&self) -> usize {
+        self.root.metrics().newlines + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len_bytes() == 0
+    }
+
+    pub fn height(&self) -> usize {
+        self.root.height()
+    }
+
+    pub fn char(&self, char_index: usize) -> Option<char> {
+        self.root.char_at(char_index)
+    }
+
+    // panics if char_index is past the end, like String::insert_str does
+    pub fn insert(&mut self, char_index: usize, text: &str) {
+        assert!(
+            char_index <= self.len_chars(),
+            "char index {} is out of bounds of a rope of {} chars",
+            char_index,
+            self.len_chars()
+        );
+        if text.is_empty() {
+            return;
+        }
+        let root = mem::replace(&mut self.root, Node::leaf(String::new()));
+        let (before, after) = root.split(char_index);
+        self.root = Node::join(Node::join(before, Node::build(text)), after);
+    }
+
+    // panics if the range is reversed or goes past the end, like String::drain does
+    pub fn remove(&mut self, chars: Range<usize>) {
+        assert!(
+            chars.start <= chars.end && chars.end <= self.len_chars(),
+            "char range {:?} is out of bounds of a rope of {} chars",
+            chars,
+            self.len_chars()
+        );
+        let root = mem::replace(&mut self.root, Node::leaf(String::new()));
+        let (before, rest) = root.split(chars.start);
+        let (_, after) = rest.split(chars.end - chars.start);
+        self.root = Node::join(before, after);
+    }
+
+    // the text as borrowed chunks, one per leaf, without copying anything
+    pub fn chunks(&self) -> Chunks<'_> {
+        self.slice(0..self.len_chars())
+    }
+
+    // the chars in the range, as borrowed chunks; None if the range doesn't fit
+    pub fn get_slice(&self, chars: Range<usize>) -> Option<Chunks<'_>> {
+        if chars.start > chars.end || chars.end > self.len_chars() {
+            return None;
+        }
+        Some(self.slice(chars))
+    }
+
+    fn slice(&self, chars: Range<usize>) -> Chunks<'_> {
+        Chunks {
+            stack: vec![(&self.root, 0)],
+            range: chars,
+        }
+    }
+
+    // the char index where a line starts, counting lines from 0
+    pub fn line_to_char(&self, line: usize) -> Option<usize> {
+        match line {
+            0 => Some(0),
+            line if line < self.len_lines() => Some(self.root.after_newline(line - 1)),
+            _ => None,
+        }
+    }
+
+    // which line the char at char_index is on; the end of the text is on the last line
+    pub fn char_to_line(&self, char_index: usize) -> Option<usize> {
+        if char_index > self.len_chars() {
+            return None;
+        }
+        Some(self.root.newlines_before(char_index))
+    }
+
+    // the text of one line, without its newline
+    pub fn line(&self, line: usize) -> Option<Chunks<'_>> {
+        let start = self.line_to_char(line)?;
+        let end = match self.line_to_char(line + 1) {
+            Some(next) => next - 1,
+            None => self.len_chars(),
+        };
+        Some(self.slice(start..end))
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Rope {
+        Rope::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Rope {
+        Rope {
+            root: Node::build(text),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Rope {
+        Rope::from(text.as_str())
+    }
+}
+
+impl From<&Rope> for String {
+    fn from(rope: &Rope) -> String {
+        let mut text = String::with_capacity(rope.len_bytes());
+        text.extend(rope.chunks());
+        text
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+// walks the leaves from left to right, skipping every subtree that is outside of the range
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+    // nodes still to visit, with the char index each one starts at
+    stack: Vec<(&'a Node, usize)>,
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while let Some((node, start)) = self.stack.pop() {
+            let end = start + node.chars();
+            if end <= self.range.start || start >= self.range.end {
+                continue;
+            }
+            match node {
+                Node::Branch { left, right, .. } => {
+                    self.stack.push((right, start + left.chars()));
+                    self.stack.push((left, start));
+                }
+                Node::Leaf { text, .. } => {
+                    let from = byte_of_char(text, self.range.start.saturating_sub(start));
+                    let to = byte_of_char(text, self.range.end - start);
+                    return Some(&text[from..to]);
+                }
+            }
+        }
+        None
+    }
+}
+
+// the same operations on a plain String, to check the rope against
+struct Model {
+    text: String,
+}
+
+impl Model {
+    fn byte(&self, char_index: usize) -> usize {
+        byte_of_char(&self.text, char_index)
+    }
+
+    fn insert(&mut self, char_index: usize, text: &str) {
+        let byte = self.byte(char_index);
+        self.text.insert_str(byte, text);
+    }
+
+    fn remove(&mut self, chars: Range<usize>) {
+        let bytes = self.byte(chars.start)..self.byte(chars.end);
+        self.text.drain(bytes);
+    }
+
+    fn slice(&self, chars: Range<usize>) -> &str {
+        &self.text[self.byte(chars.start)..self.byte(chars.end)]
+    }
+}
+
+fn collect(chunks: Chunks) -> String {
+    chunks.collect()
+}
+
+// random inserts and removes on a Rope and a String side by side, checking they always agree
+fn fuzz(seed: u64, operations: usize) {
+    let pieces = ["a", "hello ", "é", "🦀", "\n", "line\n", "東京", "\r\n", ""];
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut rope = Rope::new();
+    let mut model = Model {
+        text: String::new(),
+    };
+    for _ in 0..operations {
+        let len = rope.len_chars();
+        if len == 0 || rng.gen_bool(0.6) {
+            // sometimes a long piece, so inserts have to build more than one leaf
+            let repeat = if rng.gen_bool(0.05) {
+                rng.gen_range(100..400)
+            } else {
+                rng.gen_range(1..4)
+            };
+            let text = pieces[rng.gen_range(0..pieces.len())].repeat(repeat);
+            let at = rng.gen_range(0..=len);
+            rope.insert(at, &text);
+            model.insert(at, &text);
+        } else {
+            let start = rng.gen_range(0..=len);
+            let end = rng.gen_range(start..=len.min(start + 200));
+            rope.remove(start..end);
+            model.remove(start..end);
+        }
+
+        let len = rope.len_chars();
+        assert_eq!(len, model.text.chars().count());
+        assert_eq!(rope.len_bytes(), model.text.len());
+        assert!(rope.root.is_valid());
+
+        let start = rng.gen_range(0..=len);
+        let end = rng.gen_range(start..=len);
+        assert_eq!(collect(rope.slice(start..end)), model.slice(start..end));
+        assert_eq!(rope.char(start), model.text.chars().nth(start));
+
+        let lines: Vec<&str> = model.text.split('\n').collect();
+        assert_eq!(rope.len_lines(), lines.len());
+        let line = rng.gen_range(0..lines.len());
+        assert_eq!(collect(rope.line(line).unwrap()), lines[line]);
+        let line_start = rope.line_to_char(line).unwrap();
+        assert_eq!(rope.char_to_line(line_start), Some(line));
+    }
+    assert_eq!(String::from(&rope), model.text);
+}
+
+pub fn rope() {
+    let mut rope = Rope::from("hello\nworld");
+    assert_eq!((rope.len_chars(), rope.len_lines()), (11, 2));
+    rope.insert(5, ", dear");
+    rope.insert(0, "¡");
+    assert_eq!(rope.to_string(), "¡hello, dear\nworld");
+    rope.remove(6..12);
+    assert_eq!(rope.to_string(), "¡hello\nworld");
+
+    // slicing counts chars, not bytes, so it can't land inside the 2 bytes of '¡'
+    assert_eq!(collect(rope.get_slice(0..3).unwrap()), "¡he");
+    assert!(rope.get_slice(5..20).is_none());
+    assert_eq!(rope.char(0), Some('¡'));
+
+    assert_eq!(collect(rope.line(1).unwrap()), "world");
+    assert_eq!(rope.line_to_char(1), Some(7));
+    assert_eq!(rope.char_to_line(9), Some(1));
+    assert_eq!(rope.line(2).map(collect), None);
+
+    // a big text is split into leaves, and stays a shallow tree however many edits are made
+    let line = "the quick brown fox jumps over the lazy dog 🦊\n";
+    let mut big = Rope::from(line.repeat(10_000).as_str());
+    assert_eq!(big.len_lines(), 10_001);
+    assert!(big.height() <= 12);
+    for i in 0..1000 {
+        big.insert(i * 37, "✏️");
+    }
+    assert!(big.height() <= 14);
+    assert!(big.root.is_valid());
+    assert_eq!(collect(big.line(5_000).unwrap()), line.trim_end());
+
+    // the chunks are slices of the leaves themselves: collecting them is the only copy
+    let chunks: Vec<&str> = big.chunks().collect();
+    assert!(chunks.len() > 900);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_LEAF));
+    assert_eq!(chunks.concat().len(), big.len_bytes());
+
+    fuzz(1, 2_000);
+    fuzz(2, 2_000);
+
+    println!(
+        "{} lines, {} chars in {} chunks, {} levels deep",
+        big.len_lines(),
+        big.len_chars(),
+        chunks.len(),
+        big.height()
+    );
+}
@@ -0,0 +1,346 @@
+use std::cmp::Ordering;
+use std::iter::FusedIterator;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::counting_alloc;
+
+// slices.rs ends with `&a[1..3]`: a slice borrows part of an array without copying it
+// everything here works the same way, handing out subslices of the slice it was given or
+// rearranging it in place, so none of it allocates:
+//
+//     split_by(&[1, 0, 2, 3], |&x| x == 0)    [1], [2, 3]
+//     exact_chunks(&[1, 2, 3, 4, 5], 2)      [1, 2], [3, 4], and [5] left over
+//     windows_step(&[1, 2, 3, 4, 5], 3, 2)   [1, 2, 3], [3, 4, 5]
+//     rotate_left(&mut [1, 2, 3, 4], 1)      [2, 3, 4, 1]
+//     stable_partition(&mut s, pred)        the elements pred says yes to first, both halves still in order
+//     lower_bound / upper_bound             where a value goes in a sorted slice
+
+// the runs between the elements that match, like str::split; separators next to each other give empty runs
+pub fn split_by<T, F: FnMut(&T) -> bool>(slice: &[T], is_separator: F) -> SplitBy<'_, T, F> {
+    SplitBy {
+        rest: Some(slice),
+        is_separator,
+    }
+}
+
+pub struct SplitBy<'a, T, F> {
+    // None once the last run has been handed out
+    rest: Option<&'a [T]>,
+    is_separator: F,
+}
+
+impl<'a, T, F: FnMut(&T) -> bool> Iterator for SplitBy<'a, T, F> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        let rest = self.rest?;
+        match rest.iter().position(&mut self.is_separator) {
+            Some(i) => {
+                self.rest = Some(&rest[i + 1..]);
+                Some(&rest[..i])
+            }
+            None => {
+                self.rest = None;
+                Some(rest)
+            }
+        }
+    }
+}
+
+impl<T, F: FnMut(&T) -> bool> FusedIterator for SplitBy<'_, T, F> {}
+
+// chunks of exactly size elements, and whatever is left over at the end (shorter than size)
+pub fn exact_chunks<T>(slice: &[T], size: usize) -> (ExactChunks<'_, T>, &[T]) {
+    assert!(size != 0, "chunk size must not be zero");
+    let (chunks, remainder) = slice.split_at(slice.len() - slice.len() % size);
+    (ExactChunks { rest: chunks, size }, remainder)
+}
+
+pub struct ExactChunks<'a, T> {
+    rest: &'a [T],
+    size: usize,
+}
+
+impl<'a, T> Iterator for ExactChunks<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.rest.split_at(self.size);
+        self.rest = rest;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.rest.len() / self.size;
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for ExactChunks<'_, T> {}
+
+impl<T> FusedIterator for ExactChunks<'_, T> {}
+
+// windows of size elements whose starts are step apart; with step 1 this is slice.windows(size)
+// a window that would run past the end is left out
+pub fn windows_step<T>(slice: &[T], size: usize, step: usize) -> WindowsStep<'_, T> {
+    assert!(size != 0, "window size must not be zero");
+    assert!(step != 0, "step must not be zero");
+    WindowsStep {
+        rest: slice,
+        size,
+        step,
+    }
+}
+
+pub struct WindowsStep<'a, T> {
+    rest: &'a [T],
+    size: usize,
+    step: usize,
+}
+
+impl<'a, T> Iterator for WindowsStep<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        if self.rest.len() < self.size {
+            return None;
+        }
+        let window = &self.rest[..self.size];
+        self.rest = self.rest.get(self.step..).unwrap_or(&[]);
+        Some(window)
+    }
+}
+
+impl<T> FusedIterator for WindowsStep<'_, T> {}
+
+// moves the first mid elements to the end, by reversing both parts and then the whole slice
+pub fn rotate_left<T>(slice: &mut [T], mid: usize) {
+    assert!(mid <= slice.len(), "mid is past the end of the slice");
+    slice[..mid].reverse();
+    slice[mid..].reverse();
+    slice.reverse();
+}
+
+// moves the last k elements to the front
+pub fn rotate_right<T>(slice: &mut [T], k: usize) {
+    assert!(k <= slice.len(), "k is past the end of the slice");
+    let mid = slice.len() - k;
+    rotate_left(slice, mid);
+}
+
+// puts the elements pred says yes to before the others, keeping both groups in their original order,
+// and returns how many said yes
+// without a second buffer this takes O(n log n): each half is partitioned on its own, and then the
+// "no" part of the left half and the "yes" part of the right half trade places with one rotation
+pub fn stable_partition<T, F: FnMut(&T) -> bool>(slice: &mut [T], mut pred: F) -> usize {
+    fn partition<T, F: FnMut(&T) -> bool>(slice: &mut [T], pred: &mut F) -> usize {
+        match slice.len() {
+            0 => 0,
+            1 => usize::from(pred(&slice[0])),
+            len => {
+                let mid = len / 2;
+                let (left, right) = slice.split_at_mut(mid);
+                let left_yes = partition(left, pred);
+                let right_yes = partition(right, pred);
+                rotate_left(&mut slice[left_yes..mid + right_yes], mid - left_yes);
+                left_yes + right_yes
+            }
+        }
+    }
+    partition(slice, &mut pred)
+}
+
+// in a sorted slice: the first index whose element is not less than value, where value could be inserted
+// before any equal elements
+pub fn lower_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    partition_point(slice, |x| x.cmp(value) == Ordering::Less)
+}
+
+// the first index whose element is greater than value, past any equal elements
+pub fn upper_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    partition_point(slice, |x| x.cmp(value) != Ordering::Greater)
+}
+
+// the binary search both of them share: pred is true for a prefix of the slice, and false after it
+fn partition_point<T>(slice: &[T], mut pred: impl FnMut(&T) -> bool) -> usize {
+    let (mut low, mut high) = (0, slice.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(&slice[mid]) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+// the obvious versions, which allocate and don't care how slow they are, to check the others against
+mod naive {
+    pub fn split_by(slice: &[i32], is_separator: impl Fn(&i32) -> bool) -> Vec<Vec<i32>> {
+        let mut runs = vec![Vec::new()];
+        for x in slice {
+            if is_separator(x) {
+                runs.push(Vec::new());
+            } else {
+                runs.last_mut().unwrap().push(*x);
+            }
+        }
+        runs
+    }
+
+    pub fn windows_step(slice: &[i32], size: usize, step: usize) -> Vec<Vec<i32>> {
+        (0..slice.len())
+            .step_by(step)
+            .filter(|start| start + size <= slice.len())
+            .map(|start| slice[start..start + size].to_vec())
+            .collect()
+    }
+
+    pub fn rotate_left(slice: &[i32], mid: usize) -> Vec<i32> {
+        slice[mid..].iter().chain(&slice[..mid]).copied().collect()
+    }
+
+    pub fn stable_partition(slice: &[i32], pred: impl Fn(&i32) -> bool) -> Vec<i32> {
+        let yes = slice.iter().filter(|x| pred(x));
+        let no = slice.iter().filter(|x| !pred(x));
+        yes.chain(no).copied().collect()
+    }
+
+    pub fn lower_bound(slice: &[i32], value: i32) -> usize {
+        slice.iter().filter(|&&x| x < value).count()
+    }
+
+    pub fn upper_bound(slice: &[i32], value: i32) -> usize {
+        slice.iter().filter(|&&x| x <= value).count()
+    }
+}
+
+fn to_vecs(slices: impl Iterator<Item = impl AsRef<[i32]>>) -> Vec<Vec<i32>> {
+    slices.map(|slice| slice.as_ref().to_vec()).collect()
+}
+
+fn check_against_naive(rng: &mut StdRng) {
+    let len = rng.gen_range(0..40);
+    let values: Vec<i32> = (0..len).map(|_| rng.gen_range(0..6)).collect();
+    let is_zero = |x: &i32| *x == 0;
+    let is_even = |x: &i32| x % 2 == 0;
+
+    assert_eq!(
+        to_vecs(split_by(&values, is_zero)),
+        naive::split_by(&values, is_zero)
+    );
+
+    let size = rng.gen_range(1..6);
+    let (chunks, remainder) = exact_chunks(&values, size);
+    assert_eq!(chunks.len(), values.len() / size);
+    assert_eq!(to_vecs(chunks), to_vecs(values.chunks_exact(size)));
+    assert_eq!(remainder, values.chunks_exact(size).remainder());
+
+    let step = rng.gen_range(1..6);
+    assert_eq!(
+        to_vecs(windows_step(&values, size, step)),
+        naive::windows_step(&values, size, step)
+    );
+
+    let mid = rng.gen_range(0..=values.len());
+    let mut rotated = values.clone();
+    rotate_left(&mut rotated, mid);
+    assert_eq!(rotated, naive::rotate_left(&values, mid));
+    rotate_right(&mut rotated, mid);
+    assert_eq!(rotated, values);
+
+    let mut partitioned = values.clone();
+    let evens = stable_partition(&mut partitioned, is_even);
+    assert_eq!(partitioned, naive::stable_partition(&values, is_even));
+    assert_eq!(evens, values.iter().filter(|x| is_even(x)).count());
+
+    let mut sorted = values.clone();
+    sorted.sort();
+    for value in -1..=7 {
+        assert_eq!(
+            lower_bound(&sorted, &value),
+            naive::lower_bound(&sorted, value)
+        );
+        assert_eq!(
+            upper_bound(&sorted, &value),
+            naive::upper_bound(&sorted, value)
+        );
+    }
+}
+
+pub fn slice_utils() {
+    // the same array as slices.rs
+    let a = [1, 2, 3, 4, 5];
+    assert_eq!(&a[1..3], &[2, 3]);
+
+    let runs: Vec<&[i32]> = split_by(&[1, 0, 2, 3, 0, 0, 4], |&x| x == 0).collect();
+    assert_eq!(runs, [&[1][..], &[2, 3], &[], &[4]]);
+    // every run is part of the original array, not a copy of it
+    let runs: Vec<&[i32]> = split_by(&a, |&x| x == 3).collect();
+    assert_eq!(runs[1].as_ptr(), a[3..].as_ptr());
+
+    let (chunks, remainder) = exact_chunks(&a, 2);
+    assert_eq!(chunks.collect::<Vec<_>>(), [[1, 2], [3, 4]]);
+    assert_eq!(remainder, &[5]);
+
+    let windows: Vec<&[i32]> = windows_step(&a, 3, 2).collect();
+    assert_eq!(windows, [[1, 2, 3], [3, 4, 5]]);
+    assert_eq!(windows_step(&a, 2, 1).count(), a.windows(2).count());
+    assert_eq!(windows_step(&a, 6, 1).count(), 0);
+
+    let mut b = a;
+    rotate_left(&mut b, 2);
+    assert_eq!(b, [3, 4, 5, 1, 2]);
+    rotate_right(&mut b[1..], 1);
+    assert_eq!(b, [3, 2, 4, 5, 1]);
+
+    // odd numbers first, and both groups keep their order
+    let mut c = [5, 2, 8, 1, 4, 7, 3, 6];
+    let odd = stable_partition(&mut c, |x| x % 2 == 1);
+    assert_eq!((odd, c), (4, [5, 1, 7, 3, 2, 8, 4, 6]));
+
+    // a stable partition keeps ties in the order they were in, which plain sorting by one key wouldn't promise
+    let mut words = ["apple", "Bee", "cat", "Dog", "eel"];
+    stable_partition(&mut words, |w| w.starts_with(char::is_uppercase));
+    assert_eq!(words, ["Bee", "Dog", "apple", "cat", "eel"]);
+
+    let sorted = [1, 2, 2, 2, 3, 5, 8];
+    assert_eq!((lower_bound(&sorted, &2), upper_bound(&sorted, &2)), (1, 4));
+    assert_eq!(
+        &sorted[lower_bound(&sorted, &2)..upper_bound(&sorted, &2)],
+        &[2, 2, 2]
+    );
+    assert_eq!((lower_bound(&sorted, &4), upper_bound(&sorted, &4)), (5, 5));
+    assert_eq!(lower_bound(&sorted, &9), sorted.len());
+    assert_eq!(upper_bound(&[] as &[i32], &0), 0);
+
+    let mut rng = StdRng::seed_from_u64(50);
+    for _ in 0..2_000 {
+        check_against_naive(&mut rng);
+    }
+
+    // with the counting allocator in, check that none of it allocates
+    if counting_alloc::is_enabled() {
+        let mut values: Vec<i32> = (0..1000).map(|_| rng.gen_range(0..100)).collect();
+        let (found, stats) = counting_alloc::measure(|| {
+            let runs = split_by(&values, |&x| x == 0).count();
+            let chunks = exact_chunks(&values, 7).0.count();
+            let windows = windows_step(&values, 10, 3).count();
+            rotate_left(&mut values, 333);
+            stable_partition(&mut values, |x| x % 3 == 0);
+            values.sort_unstable();
+            let bounds = upper_bound(&values, &50) - lower_bound(&values, &50);
+            runs + chunks + windows + bounds
+        });
+        assert!(found > 0);
+        assert_eq!(stats.allocations, 0);
+    }
+
+    println!("{:?} {:?}", windows, remainder);
+}
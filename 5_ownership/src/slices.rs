@@ -41,9 +41,14 @@ pub fn slices() {
     // We’d do so like this:
     let slice = &a[1..3];
     assert_eq!(slice, &[2, 3]);
+
+    let s = String::from("hello world");
+    assert_eq!(first_word_old(&s), 5);
+    assert_eq!(first_word(&s), "hello");
 }
 
 // old first_word function which only could return a first word's width
+#[allow(clippy::ptr_arg)]
 fn first_word_old(s: &String) -> usize {
     let bytes = s.as_bytes();
 
@@ -58,6 +63,7 @@ fn first_word_old(s: &String) -> usize {
 
 // With all this information in mind, let’s rewrite first_word to return a slice.
 // The type that signifies “string slice” is written as &str:
+#[allow(clippy::ptr_arg)]
 fn first_word(s: &String) -> &str {
     let bytes = s.as_bytes();
 
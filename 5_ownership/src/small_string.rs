@@ -0,0 +1,354 @@
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::mem::size_of;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::slice;
+use std::str;
+
+// main.rs says a String is ptr, len and capacity on the stack, with the bytes on the heap
+// that is 24 bytes on a 64-bit machine, and "hi" still costs an allocation
+// a SmallString is the same 24 bytes, but uses them in one of two ways:
+//
+//     inline:  [ b y t e s . . . (23 of them)                 | tag and len ]
+//     heap:    [ ptr          | len          | capacity                     ]
+//
+// up to 23 bytes of text live right inside the value, so there is nothing to allocate or free
+// longer text spills to the heap, and from then on it works like a String
+//
+// the last byte tells the two apart: the top byte of a capacity is never above 0x7f,
+// since nothing can allocate more than isize::MAX bytes, so an inline string sets the top bit
+
+const INLINE_CAPACITY: usize = size_of::<usize>() * 3 - 1;
+const INLINE_TAG: u8 = 0x80;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Inline {
+    bytes: [u8; INLINE_CAPACITY],
+    tag_and_len: u8,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Heap {
+    ptr: NonNull<u8>,
+    len: usize,
+    // stored little-endian, so its top byte is the last byte on every machine
+    capacity: [u8; size_of::<usize>()],
+}
+
+// both views take up the whole 24 bytes, and the last byte is always initialized
+#[repr(C)]
+union Repr {
+    inline: Inline,
+    heap: Heap,
+}
+
+pub struct SmallString {
+    repr: Repr,
+}
+
+fn layout(capacity: usize) -> Layout {
+    Layout::array::<u8>(capacity).expect("capacity overflow")
+}
+
+impl SmallString {
+    pub const INLINE_CAPACITY: usize = INLINE_CAPACITY;
+
+    pub fn new() -> SmallString {
+        SmallString {
+            repr: Repr {
+                inline: Inline {
+                    bytes: [0; INLINE_CAPACITY],
+                    tag_and_len: INLINE_TAG,
+                },
+            },
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        // SAFETY: the last byte is initialized in both views
+        unsafe { self.repr.inline.tag_and_len & INLINE_TAG != 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        // SAFETY: the tag says which view holds the string
+        unsafe {
+            if self.is_inline() {
+                usize::from(self.repr.inline.tag_and_len & !INLINE_TAG)
+            } else {
+                self.repr.heap.len
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // how many bytes fit before the next allocation
+    pub fn capacity(&self) -> usize {
+        if self.is_inline() {
+            INLINE_CAPACITY
+        } else {
+            // SAFETY: not inline, so the heap view is the live one
+            usize::from_le_bytes(unsafe { self.repr.heap.capacity })
+        }
+    }
+
+    // points into the SmallString itself while inline, and into the heap after spilling
+    pub fn as_ptr(&self) -> *const u8 {
+        // SAFETY: the tag says which view holds the string
+        unsafe {
+            if self.is_inline() {
+                self.repr.inline.bytes.as_ptr()
+            } else {
+                self.repr.heap.ptr.as_ptr()
+            }
+        }
+    }
+
+    // the same, but from &mut self, so writing through it is allowed
+    fn as_mut_ptr(&mut self) -> *mut u8 {
+        // SAFETY: the tag says which view holds the string
+        unsafe {
+            if self.is_inline() {
+                self.repr.inline.bytes.as_mut_ptr()
+            } else {
+                self.repr.heap.ptr.as_ptr()
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the first len bytes at as_ptr are always initialized, and only ever copied from a &str
+        unsafe { str::from_utf8_unchecked(slice::from_raw_parts(self.as_ptr(), self.len())) }
+    }
+
+    pub fn push_str(&mut self, string: &str) {
+        let len = self.len();
+        let new_len = len.checked_add(string.len()).expect("capacity overflow");
+        if new_len > self.capacity() {
+            // double like String does, so pushing one char at a time doesn't reallocate every time
+            self.grow(new_len.max(self.capacity() * 2));
+        }
+        // SAFETY: there are at least new_len bytes of room at the (possibly new) pointer,
+        // and string can't overlap them because we hold &mut self
+        unsafe {
+            let end = self.as_mut_ptr().add(len);
+            ptr::copy_nonoverlapping(string.as_ptr(), end, string.len());
+            self.set_len(new_len);
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    // SAFETY: the caller has initialized len bytes, and len fits in the capacity
+    unsafe fn set_len(&mut self, len: usize) {
+        if self.is_inline() {
+            self.repr.inline.tag_and_len = INLINE_TAG | len as u8;
+        } else {
+            self.repr.heap.len = len;
+        }
+    }
+
+    // moves the bytes into a heap buffer of the given capacity; the first spill copies them out of the
+    // SmallString, and after that realloc keeps the bytes and may even keep the same address
+    fn grow(&mut self, capacity: usize) {
+        let len = self.len();
+        // SAFETY: layouts match what the buffer was allocated with, and len bytes are initialized
+        let ptr = unsafe {
+            if self.is_inline() {
+                let ptr = alloc::alloc(layout(capacity));
+                if !ptr.is_null() {
+                    ptr::copy_nonoverlapping(self.repr.inline.bytes.as_ptr(), ptr, len);
+                }
+                ptr
+            } else {
+                let old = self.repr.heap.ptr.as_ptr();
+                alloc::realloc(old, layout(self.capacity()), layout(capacity).size())
+            }
+        };
+        let Some(ptr) = NonNull::new(ptr) else {
+            alloc::handle_alloc_error(layout(capacity));
+        };
+        self.repr = Repr {
+            heap: Heap {
+                ptr,
+                len,
+                capacity: capacity.to_le_bytes(),
+            },
+        };
+    }
+}
+
+impl Drop for SmallString {
+    fn drop(&mut self) {
+        if !self.is_inline() {
+            // SAFETY: the heap buffer was allocated with this capacity and nobody else points to it
+            unsafe { alloc::dealloc(self.repr.heap.ptr.as_ptr(), layout(self.capacity())) }
+        }
+    }
+}
+
+impl Clone for SmallString {
+    // an inline string is copied byte for byte; a spilled one gets its own buffer, sized to fit
+    fn clone(&self) -> SmallString {
+        if self.is_inline() {
+            // SAFETY: the inline view is Copy and owns nothing
+            return SmallString {
+                repr: Repr {
+                    inline: unsafe { self.repr.inline },
+                },
+            };
+        }
+        SmallString::from(self.as_str())
+    }
+}
+
+impl Deref for SmallString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Default for SmallString {
+    fn default() -> SmallString {
+        SmallString::new()
+    }
+}
+
+impl From<&str> for SmallString {
+    fn from(string: &str) -> SmallString {
+        // both cases write the new string in place, without going through push_str's checks
+        let len = string.len();
+        if len > INLINE_CAPACITY {
+            // SAFETY: len is over INLINE_CAPACITY, so the layout isn't zero-sized
+            let ptr = unsafe { alloc::alloc(layout(len)) };
+            let Some(ptr) = NonNull::new(ptr) else {
+                alloc::handle_alloc_error(layout(len));
+            };
+            // SAFETY: the new buffer has room for len bytes, and can't overlap string
+            unsafe { ptr::copy_nonoverlapping(string.as_ptr(), ptr.as_ptr(), len) };
+            return SmallString {
+                repr: Repr {
+                    heap: Heap {
+                        ptr,
+                        len,
+                        capacity: len.to_le_bytes(),
+                    },
+                },
+            };
+        }
+        let mut small = SmallString::new();
+        // SAFETY: a new SmallString is inline, and len fits in its bytes
+        unsafe {
+            small.repr.inline.bytes[..len].copy_from_slice(string.as_bytes());
+            small.repr.inline.tag_and_len = INLINE_TAG | len as u8;
+        }
+        small
+    }
+}
+
+impl fmt::Display for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Debug for SmallString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for SmallString {
+    fn eq(&self, other: &SmallString) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmallString {}
+
+impl PartialEq<str> for SmallString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmallString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+// SAFETY: a SmallString owns its buffer outright, like a String does
+unsafe impl Send for SmallString {}
+unsafe impl Sync for SmallString {}
+
+pub fn small_string() {
+    assert_eq!(size_of::<SmallString>(), size_of::<String>());
+    assert_eq!(SmallString::INLINE_CAPACITY, 23);
+
+    // short text lives inside the value: the pointer points into the SmallString itself
+    let mut s = SmallString::from("hello");
+    assert!(s.is_inline());
+    assert_eq!((s.len(), s.capacity()), (5, 23));
+    let inside = &s as *const SmallString as usize..&s as *const SmallString as usize + 24;
+    assert!(inside.contains(&(s.as_ptr() as usize)));
+
+    s.push_str(", world!");
+    s.push('🦀');
+    assert_eq!(s, "hello, world!🦀");
+    assert_eq!(s.len(), 17);
+    assert!(s.is_inline());
+
+    // Deref<Target = str> gives it every &str method, and lets it go wherever a &str is expected
+    assert!(s.starts_with("hello"));
+    assert_eq!(s.find(','), Some(5));
+    assert_eq!(&s[7..12], "world");
+    let borrowed: &str = &s;
+    assert_eq!(borrowed.chars().count(), 14);
+
+    // one byte past the inline capacity spills to the heap, like String::from in main.rs always does
+    s.push_str(" and more");
+    assert!(!s.is_inline());
+    assert_eq!(s, "hello, world!🦀 and more");
+    assert_eq!((s.len(), s.capacity()), (26, 46));
+    assert!(!inside.contains(&(s.as_ptr() as usize)));
+    for _ in 0..10 {
+        s.push_str("!!!!!");
+    }
+    assert_eq!(s.len(), 76);
+    assert_eq!(s.capacity(), 92);
+
+    // clone copies the heap bytes into a buffer of its own, like the s1.clone() in main.rs
+    let copy = s.clone();
+    assert_eq!(copy, s);
+    assert_ne!(copy.as_ptr(), s.as_ptr());
+    assert_eq!(copy.capacity(), 76);
+    let small = SmallString::from("tiny");
+    let small_copy = small.clone();
+    assert!(small_copy.is_inline());
+    assert_eq!(small_copy, small);
+
+    // a move copies the 24 bytes and nothing else; the heap buffer stays where it was
+    let heap_bytes = s.as_ptr();
+    let moved = s;
+    assert_eq!(moved.as_ptr(), heap_bytes);
+    // println!("{}", s); // error: borrow of moved value: `s`
+
+    let exact = SmallString::from("exactly twenty-three!!!");
+    assert_eq!(exact.len(), 23);
+    assert!(exact.is_inline());
+    assert!(!SmallString::from("twenty-four bytes long!!").is_inline());
+    assert!(SmallString::new().is_empty());
+    assert_eq!(format!("{:?}", small), "\"tiny\"");
+
+    // benches/small_string.rs times these against String: `cargo bench --bench small_string`
+}
@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+
+use crate::counting_alloc::{self, Stats};
+
+// takes_and_gives_back and calculate_length(s: String) -> (String, usize) in main.rs pass a String in and
+// get it back out, so the caller keeps ownership without a clone
+// a Cow<str> ("clone on write") goes one better: it starts out as a borrowed &str, and only becomes an
+// owned String when something actually has to change, so text that is already clean is never copied
+//
+//     Text::new(input).trim().lowercase().escape_html().into_cow()
+//
+// each step either leaves the text alone, borrowed or not, or makes one new String
+// allocations() counts the Strings the steps made; with `--features count-allocations` the demo below
+// also measures the pipeline with counting_alloc.rs, so the zero-copy paths are checked against the real
+// allocator and not just against that count
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Text<'a> {
+    text: Cow<'a, str>,
+    allocations: usize,
+}
+
+impl<'a> Text<'a> {
+    pub fn new(text: &'a str) -> Text<'a> {
+        Text {
+            text: Cow::Borrowed(text),
+            allocations: 0,
+        }
+    }
+
+    // still a slice of the text Text::new was given
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.text, Cow::Borrowed(_))
+    }
+
+    // how many Strings the steps so far have made
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_cow(self) -> Cow<'a, str> {
+        self.text
+    }
+
+    fn changed(mut self, text: String) -> Text<'a> {
+        self.text = Cow::Owned(text);
+        self.allocations += 1;
+        self
+    }
+
+    // never allocates: a borrowed text gets a shorter slice, and an owned one is trimmed in place
+    pub fn trim(mut self) -> Text<'a> {
+        match &mut self.text {
+            Cow::Borrowed(text) => *text = text.trim(),
+            Cow::Owned(text) => {
+                let end = text.trim_end().len();
+                text.truncate(end);
+                let start = text.len() - text.trim_start().len();
+                text.drain(..start);
+            }
+        }
+        self
+    }
+
+    pub fn replace(self, from: &str, to: &str) -> Text<'a> {
+        if from == to || !self.text.contains(from) {
+            return self;
+        }
+        let replaced = self.text.replace(from, to);
+        self.changed(replaced)
+    }
+
+    // Unicode lowercasing, like str::to_lowercase; this isn't full case folding, which would also turn
+    // "ß" into "ss" so that "Straße" and "STRASSE" compare equal
+    pub fn lowercase(self) -> Text<'a> {
+        let unchanged = self
+            .text
+            .chars()
+            .all(|c| c.to_lowercase().eq(std::iter::once(c)));
+        if unchanged {
+            return self;
+        }
+        let lowercase = self.text.to_lowercase();
+        self.changed(lowercase)
+    }
+
+    // for putting text into HTML: the five characters that could start markup become entities
+    pub fn escape_html(self) -> Text<'a> {
+        let needs_escaping = |c: char| matches!(c, '&' | '<' | '>' | '"' | '\'');
+        let Some(first) = self.text.find(needs_escaping) else {
+            return self;
+        };
+        // everything before the first special character is copied as it is
+        let mut escaped = String::with_capacity(self.text.len() + 16);
+        escaped.push_str(&self.text[..first]);
+        for c in self.text[first..].chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+        }
+        self.changed(escaped)
+    }
+}
+
+// the same thing as a plain function: a clean username comes back borrowed, anything else as a new String
+pub fn normalize_username(username: &str) -> Cow<'_, str> {
+    Text::new(username)
+        .trim()
+        .lowercase()
+        .replace(" ", "_")
+        .into_cow()
+}
+
+// what calculate_length in main.rs would look like without handing ownership back and forth
+fn calculate_length(s: &str) -> usize {
+    s.len()
+}
+
+pub fn text_pipeline() {
+    // text that needs nothing done is never copied: it comes out as a slice of what went in
+    let input = "  hello, world  ";
+    let text = Text::new(input).trim().lowercase().escape_html();
+    assert!(text.is_borrowed());
+    assert_eq!(text.allocations(), 0);
+    assert_eq!(text.as_str(), "hello, world");
+    assert_eq!(text.as_str().as_ptr(), input[2..].as_ptr());
+
+    // each step that changes something makes one String, and no more
+    let text = Text::new("  Hello, <World> & Friends  ")
+        .trim()
+        .lowercase()
+        .escape_html();
+    assert!(!text.is_borrowed());
+    assert_eq!(text.allocations(), 2);
+    assert_eq!(text.as_str(), "hello, &lt;world&gt; &amp; friends");
+
+    // trimming an owned String happens in place, so its buffer is reused
+    let text = Text::new("SHOUTING ").lowercase();
+    let buffer = text.as_str().as_ptr();
+    let text = text.trim();
+    assert_eq!(text.as_str(), "shouting");
+    assert_eq!(text.as_str().as_ptr(), buffer);
+    assert_eq!(text.allocations(), 1);
+    let text = Text::new("\t  tabs and spaces\u{a0}").lowercase().trim();
+    assert_eq!(text.as_str(), "tabs and spaces");
+    assert_eq!(text.allocations(), 0);
+
+    // replace only allocates when the pattern is there and would change something
+    let text = Text::new("one two three")
+        .replace("four", "4")
+        .replace("two", "two");
+    assert!(text.is_borrowed());
+    let text = Text::new("one two three").replace(" ", "-");
+    assert_eq!((text.as_str(), text.allocations()), ("one-two-three", 1));
+
+    // lowercase checks every char, not just ASCII
+    assert!(Text::new("köln 東京 🦀").lowercase().is_borrowed());
+    assert_eq!(Text::new("KÖLN").lowercase().as_str(), "köln");
+    assert_eq!(Text::new("ΣΊΣΥΦΟΣ").lowercase().as_str(), "σίσυφος");
+
+    let text = Text::new(r#"it's "quoted""#).escape_html();
+    assert_eq!(text.as_str(), "it&#39;s &quot;quoted&quot;");
+    assert!(Text::new("plain text").escape_html().is_borrowed());
+
+    // a whole batch: only the usernames that need cleaning cost an allocation
+    let usernames = ["alice", " Bob ", "carol", "Dave Smith", "eve"];
+    let normalized: Vec<Cow<str>> = usernames.iter().map(|u| normalize_username(u)).collect();
+    assert_eq!(normalized, ["alice", "bob", "carol", "dave_smith", "eve"]);
+    let copied = normalized
+        .iter()
+        .filter(|name| matches!(name, Cow::Owned(_)))
+        .count();
+    assert_eq!(copied, 2);
+
+    // a Cow derefs to &str, so it can be borrowed like any other string, and kept with into_owned when needed
+    let length = calculate_length(&normalized[3]);
+    assert_eq!(length, 10);
+    let kept: String = normalize_username("alice").into_owned();
+    assert_eq!(kept, "alice");
+
+    // the allocator agrees: the clean paths ask for nothing at all, and the others for exactly what
+    // allocations() says
+    if counting_alloc::is_enabled() {
+        let (text, clean) = counting_alloc::measure(|| {
+            Text::new("  hello, world  ")
+                .trim()
+                .lowercase()
+                .replace("four", "4")
+                .escape_html()
+        });
+        assert!(text.is_borrowed());
+        assert_eq!(clean, Stats::default());
+
+        let (text, changed) = counting_alloc::measure(|| {
+            Text::new("  Hello, <World> & Friends  ")
+                .trim()
+                .lowercase()
+                .escape_html()
+        });
+        assert_eq!(changed.allocations, text.allocations());
+        assert_eq!((changed.allocations, changed.deallocations), (2, 1));
+
+        let (_, trimmed) = counting_alloc::measure(|| Text::new("SHOUTING ").lowercase().trim());
+        assert_eq!(trimmed.allocations, 1);
+        assert_eq!(trimmed.reallocations, 0);
+
+        let (normalized, batch) = counting_alloc::measure(|| {
+            usernames
+                .iter()
+                .map(|u| normalize_username(u))
+                .collect::<Vec<_>>()
+        });
+        // one for the Vec, one for " Bob " (lowercase), and two for "Dave Smith" (lowercase, then replace)
+        assert_eq!(batch.allocations, 4);
+        assert_eq!(normalized.len(), 5);
+    }
+
+    println!("{:?}", normalized);
+}
@@ -0,0 +1,255 @@
+use std::iter::FusedIterator;
+
+use unicode_segmentation::{UnicodeSegmentation, UnicodeWords};
+
+// first_word in slices.rs only looks for the byte b' ', so "hello\tworld", "hello\u{a0}world" and "hello, world"
+// all come back whole, or with the comma attached
+// a Tokenizer finds words by whole characters instead, so the slices it hands out always start and end on
+// a character boundary, and they borrow from the text like first_word's do: nothing is copied
+//
+//     Tokenizer::whitespace()              splits on any Unicode whitespace
+//     Tokenizer::separated_by(&[',', ';']) splits on the given characters
+//     Tokenizer::split_when(f)             splits on every character f says yes to
+//     Tokenizer::unicode()                 uses the Unicode word boundary rules (UAX #29): punctuation is
+//                                          dropped, but "can't" and "3.14" stay in one piece
+
+#[derive(Debug, Clone)]
+enum Separators {
+    Whitespace,
+    Chars(Vec<char>),
+    Predicate(fn(char) -> bool),
+    Unicode,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    separators: Separators,
+}
+
+impl Tokenizer {
+    pub fn whitespace() -> Tokenizer {
+        Tokenizer {
+            separators: Separators::Whitespace,
+        }
+    }
+
+    pub fn separated_by(separators: &[char]) -> Tokenizer {
+        Tokenizer {
+            separators: Separators::Chars(separators.to_vec()),
+        }
+    }
+
+    pub fn split_when(is_separator: fn(char) -> bool) -> Tokenizer {
+        Tokenizer {
+            separators: Separators::Predicate(is_separator),
+        }
+    }
+
+    pub fn unicode() -> Tokenizer {
+        Tokenizer {
+            separators: Separators::Unicode,
+        }
+    }
+
+    fn is_separator(&self, c: char) -> bool {
+        match &self.separators {
+            Separators::Whitespace => c.is_whitespace(),
+            Separators::Chars(chars) => chars.contains(&c),
+            Separators::Predicate(is_separator) => is_separator(c),
+            Separators::Unicode => unreachable!("unicode words don't use separators"),
+        }
+    }
+
+    // the words of text in order, from either end; empty words between two separators are skipped
+    pub fn words<'t, 'a>(&'t self, text: &'a str) -> Words<'t, 'a> {
+        let inner = match self.separators {
+            Separators::Unicode => Inner::Unicode(text.unicode_words()),
+            _ => Inner::Split {
+                rest: text,
+                tokenizer: self,
+            },
+        };
+        Words { inner }
+    }
+
+    pub fn first_word<'a>(&self, text: &'a str) -> Option<&'a str> {
+        self.words(text).next()
+    }
+
+    pub fn last_word<'a>(&self, text: &'a str) -> Option<&'a str> {
+        self.words(text).next_back()
+    }
+
+    // counting from 0, like nth on any iterator
+    pub fn nth_word<'a>(&self, text: &'a str, n: usize) -> Option<&'a str> {
+        self.words(text).nth(n)
+    }
+}
+
+impl Default for Tokenizer {
+    fn default() -> Tokenizer {
+        Tokenizer::unicode()
+    }
+}
+
+#[derive(Debug)]
+enum Inner<'t, 'a> {
+    // the part of the text not handed out yet, from both ends
+    Split {
+        rest: &'a str,
+        tokenizer: &'t Tokenizer,
+    },
+    Unicode(UnicodeWords<'a>),
+}
+
+// borrows the tokenizer for 't and the text for 'a, and only the text ends up in the words
+#[derive(Debug)]
+pub struct Words<'t, 'a> {
+    inner: Inner<'t, 'a>,
+}
+
+impl<'a> Iterator for Words<'_, 'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        match &mut self.inner {
+            Inner::Unicode(words) => words.next(),
+            Inner::Split { rest, tokenizer } => {
+                let text = rest.trim_start_matches(|c| tokenizer.is_separator(c));
+                if text.is_empty() {
+                    *rest = text;
+                    return None;
+                }
+                // find gives a byte index of a separator character, which is always a character boundary
+                let end = text
+                    .find(|c| tokenizer.is_separator(c))
+                    .unwrap_or(text.len());
+                let (word, after) = text.split_at(end);
+                *rest = after;
+                Some(word)
+            }
+        }
+    }
+}
+
+impl<'a> DoubleEndedIterator for Words<'_, 'a> {
+    fn next_back(&mut self) -> Option<&'a str> {
+        match &mut self.inner {
+            Inner::Unicode(words) => words.next_back(),
+            Inner::Split { rest, tokenizer } => {
+                let text = rest.trim_end_matches(|c| tokenizer.is_separator(c));
+                if text.is_empty() {
+                    *rest = text;
+                    return None;
+                }
+                let start = text
+                    .char_indices()
+                    .rev()
+                    .find(|&(_, c)| tokenizer.is_separator(c))
+                    .map_or(0, |(i, c)| i + c.len_utf8());
+                let (before, word) = text.split_at(start);
+                *rest = before;
+                Some(word)
+            }
+        }
+    }
+}
+
+impl FusedIterator for Words<'_, '_> {}
+
+// with the Unicode word rules, which is what first_word in slices.rs is usually meant to do
+pub fn words(text: &str) -> impl DoubleEndedIterator<Item = &str> {
+    text.unicode_words()
+}
+
+pub fn first_word(text: &str) -> Option<&str> {
+    Tokenizer::unicode().first_word(text)
+}
+
+pub fn last_word(text: &str) -> Option<&str> {
+    Tokenizer::unicode().last_word(text)
+}
+
+pub fn nth_word(text: &str, n: usize) -> Option<&str> {
+    Tokenizer::unicode().nth_word(text, n)
+}
+
+// true when word is a slice of text itself rather than a copy of part of it
+fn borrows_from(word: &str, text: &str) -> bool {
+    let range = text.as_bytes().as_ptr_range();
+    range.contains(&word.as_ptr()) && word.as_ptr() as usize + word.len() <= range.end as usize
+}
+
+pub fn tokenizer() {
+    // what first_word in slices.rs gets wrong
+    let tabbed = "hello\tworld";
+    let nbsp = "hello\u{a0}world";
+    assert_eq!(tabbed.find(' '), None);
+    assert_eq!(first_word(tabbed), Some("hello"));
+    assert_eq!(first_word(nbsp), Some("hello"));
+    assert_eq!(first_word("Hello, world!"), Some("Hello"));
+    assert_eq!(first_word("  \n  "), None);
+
+    // slicing by bytes is only safe on character boundaries: é takes bytes 1 and 2 of "héllo"
+    let accented = "héllo wörld";
+    assert!(!accented.is_char_boundary(2));
+    // let h = &accented[0..2]; // panics: byte index 2 is not a char boundary; it is inside 'é' (bytes 1..3) of `héllo wörld`
+    assert_eq!(first_word(accented), Some("héllo"));
+    assert_eq!(last_word(accented), Some("wörld"));
+
+    let text = "Grüße aus Köln — naïve café, 東京 and Zürich\tüber alles\n";
+    let all: Vec<&str> = words(text).collect();
+    assert_eq!(
+        all,
+        [
+            "Grüße", "aus", "Köln", "naïve", "café", "東", "京", "and", "Zürich", "über", "alles"
+        ]
+    );
+    assert!(all.iter().all(|word| borrows_from(word, text)));
+    assert_eq!(nth_word(text, 3), Some("naïve"));
+    assert_eq!(nth_word(text, 11), None);
+    let backwards: Vec<&str> = words(text).rev().take(2).collect();
+    assert_eq!(backwards, ["alles", "über"]);
+
+    // the Unicode rules keep contractions and numbers together, and leave emoji out
+    assert_eq!(
+        words("I can't pay 3.14 € 🦀 now").collect::<Vec<_>>(),
+        ["I", "can't", "pay", "3.14", "now"]
+    );
+
+    // splitting on whitespace only keeps the punctuation with the words
+    let whitespace = Tokenizer::whitespace();
+    let spaced = " one\u{2003}two\u{a0}three,\r\nfour ";
+    let split: Vec<&str> = whitespace.words(spaced).collect();
+    assert_eq!(split, ["one", "two", "three,", "four"]);
+    assert_eq!(whitespace.last_word(spaced), Some("four"));
+    assert_eq!(whitespace.nth_word(spaced, 2), Some("three,"));
+    // taking from both ends meets in the middle without handing out a word twice
+    let mut both = whitespace.words(spaced);
+    assert_eq!(both.next(), Some("one"));
+    assert_eq!(both.next_back(), Some("four"));
+    assert_eq!(both.next_back(), Some("three,"));
+    assert_eq!(both.next(), Some("two"));
+    assert_eq!(both.next(), None);
+    assert_eq!(both.next_back(), None);
+
+    let csv = Tokenizer::separated_by(&[',', ';']);
+    let fields: Vec<&str> = csv.words("äpfel,,birnen;kirschen;").collect();
+    assert_eq!(fields, ["äpfel", "birnen", "kirschen"]);
+    assert_eq!(csv.last_word("äpfel,,birnen;kirschen;"), Some("kirschen"));
+
+    let alphanumeric = Tokenizer::split_when(|c| !c.is_alphanumeric());
+    assert_eq!(
+        alphanumeric
+            .words("snake_case-and.dots")
+            .collect::<Vec<_>>(),
+        ["snake", "case", "and", "dots"]
+    );
+    assert_eq!(alphanumeric.last_word("ends with 🦀"), Some("with"));
+    assert_eq!(Tokenizer::default().first_word("¡Hola!"), Some("Hola"));
+
+    println!(
+        "{:?}",
+        words("hello\u{a0}world, héllo\twörld").collect::<Vec<_>>()
+    );
+}
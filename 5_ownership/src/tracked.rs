@@ -0,0 +1,328 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+// main.rs says "drop is called" and "s's value moves into the function" in comments, with nothing to see
+// a Tracked<T> wraps a value and writes down what happens to it, in order:
+//
+//     #1 main: s created
+//     #2 takes_ownership: some_string moved in from main (was s)
+//     #3 takes_ownership: some_string dropped
+//
+// a move is just a copy of some bytes, which no code gets to see, so moves are only logged
+// where the code says so with moved_to, which also names the binding the value now lives in;
+// creation, clone and drop are logged on their own
+// every Tracked made from one Tracer writes to the same log, and the sequence numbers count up from 1
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Created,
+    Cloned { from: usize },
+    // was is the name of the binding in the scope it moved out of
+    Moved { from: &'static str, was: String },
+    Dropped,
+    // into_inner took the value out, so the Tracked is gone but the value lives on untracked
+    TakenOut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub seq: usize,
+    pub id: usize,
+    pub name: String,
+    pub scope: &'static str,
+    pub action: Action,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {}: {} ", self.seq, self.scope, self.name)?;
+        match &self.action {
+            Action::Created => write!(f, "created"),
+            Action::Cloned { from } => write!(f, "cloned from value {}", from),
+            Action::Moved { from, was } if *was == self.name => write!(f, "moved in from {}", from),
+            Action::Moved { from, was } => write!(f, "moved in from {} (was {})", from, was),
+            Action::Dropped => write!(f, "dropped"),
+            Action::TakenOut => write!(f, "taken out"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Log {
+    events: Vec<Event>,
+    next_id: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Tracer {
+    log: Rc<RefCell<Log>>,
+}
+
+impl Tracer {
+    pub fn new() -> Tracer {
+        Tracer::default()
+    }
+
+    pub fn track<T>(&self, scope: &'static str, name: &str, value: T) -> Tracked<T> {
+        let id = self.record(None, name, scope, Action::Created);
+        Tracked {
+            value: Some(value),
+            id,
+            name: name.to_string(),
+            scope,
+            tracer: self.clone(),
+        }
+    }
+
+    // a new id when id is None
+    fn record(&self, id: Option<usize>, name: &str, scope: &'static str, action: Action) -> usize {
+        let mut log = self.log.borrow_mut();
+        let id = id.unwrap_or_else(|| {
+            log.next_id += 1;
+            log.next_id
+        });
+        let seq = log.events.len() + 1;
+        log.events.push(Event {
+            seq,
+            id,
+            name: name.to_string(),
+            scope,
+            action,
+        });
+        id
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.log.borrow().events.clone()
+    }
+
+    // the names of the values that have been dropped, first drop first
+    pub fn drops(&self) -> Vec<String> {
+        self.log
+            .borrow()
+            .events
+            .iter()
+            .filter(|event| event.action == Action::Dropped)
+            .map(|event| event.name.clone())
+            .collect()
+    }
+
+    // one line per value, in the order they were created
+    pub fn report(&self) -> Vec<Lifetime> {
+        let log = self.log.borrow();
+        let mut lifetimes: Vec<Lifetime> = Vec::new();
+        for event in &log.events {
+            match lifetimes
+                .iter_mut()
+                .find(|lifetime| lifetime.id == event.id)
+            {
+                None => lifetimes.push(Lifetime {
+                    id: event.id,
+                    names: vec![event.name.clone()],
+                    start: event.seq,
+                    end: None,
+                    scopes: vec![event.scope],
+                }),
+                Some(lifetime) => {
+                    if matches!(event.action, Action::Dropped | Action::TakenOut) {
+                        lifetime.end = Some(event.seq);
+                    }
+                    if lifetime.scopes.last() != Some(&event.scope) {
+                        lifetime.scopes.push(event.scope);
+                    }
+                    if lifetime.names.last() != Some(&event.name) {
+                        lifetime.names.push(event.name.clone());
+                    }
+                }
+            }
+        }
+        lifetimes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lifetime {
+    pub id: usize,
+    // every binding the value has been in, in order
+    pub names: Vec<String>,
+    pub start: usize,
+    // None while the value is still alive
+    pub end: Option<usize>,
+    // every scope the value has been in, in order
+    pub scopes: Vec<&'static str>,
+}
+
+impl fmt::Display for Lifetime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<24} #{}..", self.names.join(" -> "), self.start)?;
+        match self.end {
+            Some(end) => write!(f, "#{:<4}", end)?,
+            None => write!(f, "     ")?,
+        }
+        write!(f, " {}", self.scopes.join(" -> "))
+    }
+}
+
+pub struct Tracked<T> {
+    // only None after into_inner, so drop knows there is nothing left to log
+    value: Option<T>,
+    id: usize,
+    name: String,
+    scope: &'static str,
+    tracer: Tracer,
+}
+
+impl<T> Tracked<T> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    // logs a move into the binding name in another scope, usually in the first line of the function the
+    // value was passed to, or where the caller binds what a function returned
+    pub fn moved_to(mut self, scope: &'static str, name: &str) -> Tracked<T> {
+        let was = std::mem::replace(&mut self.name, name.to_string());
+        self.tracer.record(
+            Some(self.id),
+            &self.name,
+            scope,
+            Action::Moved {
+                from: self.scope,
+                was,
+            },
+        );
+        self.scope = scope;
+        self
+    }
+
+    // gives up the tracking and hands back the plain value
+    pub fn into_inner(mut self) -> T {
+        self.tracer
+            .record(Some(self.id), &self.name, self.scope, Action::TakenOut);
+        self.value.take().unwrap()
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T: Clone> Clone for Tracked<T> {
+    // a clone is a new value with its own id, named after the one it was cloned from
+    fn clone(&self) -> Tracked<T> {
+        let name = format!("{}.clone()", self.name);
+        let id = self
+            .tracer
+            .record(None, &name, self.scope, Action::Cloned { from: self.id });
+        Tracked {
+            value: self.value.clone(),
+            id,
+            name,
+            scope: self.scope,
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        if self.value.is_some() {
+            self.tracer
+                .record(Some(self.id), &self.name, self.scope, Action::Dropped);
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({:?})", self.name, &**self)
+    }
+}
+
+struct Pair {
+    first: Tracked<&'static str>,
+    second: Tracked<&'static str>,
+}
+
+pub fn tracked() {
+    let tracer = Tracer::new();
+
+    // locals are dropped in reverse order, at the closing bracket of their scope
+    {
+        let _a = tracer.track("block", "a", 1);
+        let _b = tracer.track("block", "b", 2);
+        {
+            let _c = tracer.track("inner", "c", 3);
+        }
+        assert_eq!(tracer.drops(), ["c"]);
+    }
+    assert_eq!(tracer.drops(), ["c", "b", "a"]);
+
+    // but the fields of a struct, and the elements of a Vec, are dropped first to last
+    let tracer = Tracer::new();
+    {
+        let pair = Pair {
+            first: tracer.track("pair", "first", "1st"),
+            second: tracer.track("pair", "second", "2nd"),
+        };
+        let list: Vec<Tracked<usize>> = ["[0]", "[1]"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| tracer.track("list", name, i))
+            .collect();
+        assert_eq!((*pair.first, *pair.second, list.len()), ("1st", "2nd", 2));
+    }
+    assert_eq!(tracer.drops(), ["[0]", "[1]", "first", "second"]);
+
+    // shadowing doesn't drop anything: the first s lives on, unreachable, until the end of the scope
+    // assigning to a variable does drop the value it held, right away
+    let tracer = Tracer::new();
+    {
+        let s = tracer.track("main", "s", 1);
+        let s = tracer.track("main", "shadow", *s + 1);
+        assert_eq!(*s, 2);
+        let mut t = tracer.track("main", "t", 10);
+        t = tracer.track("main", "t2", *t + 1);
+        assert_eq!(tracer.drops(), ["t"]);
+        assert_eq!(*t, 11);
+    }
+    assert_eq!(tracer.drops(), ["t", "t2", "shadow", "s"]);
+
+    // clone makes a second value with its own drop; into_inner ends the tracking without one
+    let tracer = Tracer::new();
+    let original = tracer.track("main", "s1", String::from("hello"));
+    let copy = original.clone();
+    assert_ne!(copy.id(), original.id());
+    let plain: String = original.into_inner();
+    drop(copy);
+    assert_eq!(plain, "hello");
+    let actions: Vec<Action> = tracer.events().into_iter().map(|e| e.action).collect();
+    assert_eq!(
+        actions,
+        [
+            Action::Created,
+            Action::Cloned { from: 1 },
+            Action::TakenOut,
+            Action::Dropped
+        ]
+    );
+    let report = tracer.report();
+    assert_eq!((report[0].start, report[0].end), (1, Some(3)));
+    assert_eq!(report[1].names, ["s1.clone()"]);
+    assert_eq!(report[1].end, Some(4));
+
+    for event in tracer.events() {
+        println!("{}", event);
+    }
+}